@@ -0,0 +1,33 @@
+//! The "core" crate defining the traits. `export_descriptor` allows other
+//! crates to implement them with `#[derive(Delegate)]`.
+
+pub mod store {
+    use auto_impl::auto_impl;
+
+    #[auto_impl(&mut, Box, export_descriptor)]
+    pub trait Store {
+        fn get(&self, key: &str) -> Option<String>;
+        fn put(&mut self, key: &str, value: String);
+        fn name() -> &'static str;
+    }
+
+    #[derive(Default)]
+    pub struct MemStore(Vec<(String, String)>);
+
+    impl Store for MemStore {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+        }
+
+        fn put(&mut self, key: &str, value: String) {
+            self.0.push((key.to_owned(), value));
+        }
+
+        fn name() -> &'static str {
+            "memory"
+        }
+    }
+}
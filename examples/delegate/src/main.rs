@@ -0,0 +1,27 @@
+//! A downstream crate implementing the `Store` trait of the core crate by
+//! delegating to a field.
+
+use auto_impl::Delegate;
+use auto_impl_delegate_example_core::store::{MemStore, Store};
+
+#[derive(Default, Delegate)]
+#[delegate(auto_impl_delegate_example_core::store::Store, to = inner)]
+struct Instrumented {
+    inner: MemStore,
+    label: &'static str,
+}
+
+fn main() {
+    let mut store = Instrumented {
+        label: "instrumented",
+        ..Default::default()
+    };
+    store.put("answer", "42".to_owned());
+
+    println!(
+        "{} ({}): answer = {:?}",
+        store.label,
+        Instrumented::name(),
+        store.get("answer"),
+    );
+}
@@ -24,6 +24,7 @@ use auto_impl::auto_impl;
 /// }
 /// ```
 #[auto_impl(&, Box)]
+#[allow(dead_code)]
 trait DisplayCollection {
     /// If the length is statically known, this is `Some(len)`.
     const LEN: Option<usize>;
@@ -48,7 +49,7 @@ fn show_first(c: impl DisplayCollection) {
     }
 }
 
-#[allow(clippy::needless_borrow)]
+#[allow(clippy::needless_borrow, clippy::needless_borrows_for_generic_args)]
 #[rustfmt::skip]
 fn main() {
     let v = vec!["dog", "cat"];
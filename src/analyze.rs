@@ -16,6 +16,10 @@ use syn::{
 /// changed.
 const PROXY_TY_PARAM_NAME: &str = "__AutoImplProxyT";
 
+/// The type parameter used for the pointer inside of `Pin<P>` if the proxy
+/// type is `Pin`. For more information see `PROXY_TY_PARAM_NAME`.
+const PROXY_PTR_PARAM_NAME: &str = "__AutoImplProxyP";
+
 /// The lifetime parameter used in the proxy type if the proxy type is `&` or
 /// `&mut`. For more information see `PROXY_TY_PARAM_NAME`.
 const PROXY_LT_PARAM_NAME: &str = "'__auto_impl_proxy_lifetime";
@@ -33,38 +37,11 @@ const PROXY_LT_PARAM_NAME: &str = "'__auto_impl_proxy_lifetime";
 /// them in a set and later check which name we can use. If we can't use a simple
 /// name, we'll use the ugly `PROXY_TY_PARAM_NAME` and `PROXY_LT_PARAM_NAME`.
 ///
-/// This method returns two idents: (type_parameter, lifetime_parameter).
-pub(crate) fn find_suitable_param_names(trait_def: &ItemTrait) -> (Ident, Lifetime) {
-    // Define the visitor that just collects names
-    struct IdentCollector<'ast> {
-        ty_names: HashSet<&'ast Ident>,
-        lt_names: HashSet<&'ast Ident>,
-    }
-
-    impl<'ast> Visit<'ast> for IdentCollector<'ast> {
-        fn visit_ident(&mut self, i: &'ast Ident) {
-            self.ty_names.insert(i);
-        }
-
-        // We overwrite this to make sure to put lifetime names into
-        // `lt_names`. We also don't recurse, so `visit_ident` won't be called
-        // for lifetime names.
-        fn visit_lifetime(&mut self, lt: &'ast Lifetime) {
-            self.lt_names.insert(&lt.ident);
-        }
-
-        // Visiting a block just does nothing. It is the default body of a method
-        // in the trait. But since that block won't be in the impl block, we can
-        // just ignore it.
-        fn visit_block(&mut self, _: &'ast Block) {}
-    }
-
-    // Create the visitor and visit the trait
-    let mut visitor = IdentCollector {
-        ty_names: HashSet::new(),
-        lt_names: HashSet::new(),
-    };
-    visit_item_trait(&mut visitor, trait_def);
+/// This method returns three idents: (type_parameter, pointer_parameter,
+/// lifetime_parameter). The pointer parameter is only used for `Pin<P>` and
+/// as the length of arrays (`[T; N]`).
+pub(crate) fn find_suitable_param_names(trait_def: &ItemTrait) -> (Ident, Ident, Lifetime) {
+    let visitor = collect_names(trait_def);
 
     fn char_to_ident(c: u8) -> Ident {
         let arr = [c];
@@ -79,6 +56,14 @@ pub(crate) fn find_suitable_param_names(trait_def: &ItemTrait) -> (Ident, Lifeti
         .find(|i| !visitor.ty_names.contains(i))
         .unwrap_or_else(|| Ident::new(PROXY_TY_PARAM_NAME, param_span()));
 
+    // Find suitable pointer type name (P..=S and A..=O), which must not be
+    // the type name we just picked
+    let ptr_name = (b'P'..=b'S')
+        .chain(b'A'..=b'O')
+        .map(char_to_ident)
+        .find(|i| !visitor.ty_names.contains(i) && *i != ty_name)
+        .unwrap_or_else(|| Ident::new(PROXY_PTR_PARAM_NAME, param_span()));
+
     // Find suitable lifetime name ('a..='z)
     let lt_name = (b'a'..=b'z')
         .map(char_to_ident)
@@ -89,7 +74,62 @@ pub(crate) fn find_suitable_param_names(trait_def: &ItemTrait) -> (Ident, Lifeti
         ident: lt_name,
     };
 
-    (ty_name, lt)
+    (ty_name, ptr_name, lt)
+}
+
+/// Returns `count` type parameter names for the elements of the tuple proxy
+/// type, derived from the given proxy type parameter (`T0`, `T1`, ...). Like
+/// the names of `find_suitable_param_names`, they don't conflict with any
+/// name used in the trait.
+pub(crate) fn find_tuple_param_names(
+    trait_def: &ItemTrait,
+    proxy_ty_param: &Ident,
+    count: usize,
+) -> Vec<Ident> {
+    let visitor = collect_names(trait_def);
+
+    (0..count)
+        .map(|i| {
+            let mut name = format!("{}{}", proxy_ty_param, i);
+            while visitor.ty_names.iter().any(|n| **n == name) {
+                name.push('_');
+            }
+            Ident::new(&name, param_span())
+        })
+        .collect()
+}
+
+/// Collects the names of all types and lifetimes used in the trait.
+struct IdentCollector<'ast> {
+    ty_names: HashSet<&'ast Ident>,
+    lt_names: HashSet<&'ast Ident>,
+}
+
+impl<'ast> Visit<'ast> for IdentCollector<'ast> {
+    fn visit_ident(&mut self, i: &'ast Ident) {
+        self.ty_names.insert(i);
+    }
+
+    // We overwrite this to make sure to put lifetime names into
+    // `lt_names`. We also don't recurse, so `visit_ident` won't be called
+    // for lifetime names.
+    fn visit_lifetime(&mut self, lt: &'ast Lifetime) {
+        self.lt_names.insert(&lt.ident);
+    }
+
+    // Visiting a block just does nothing. It is the default body of a method
+    // in the trait. But since that block won't be in the impl block, we can
+    // just ignore it.
+    fn visit_block(&mut self, _: &'ast Block) {}
+}
+
+fn collect_names(trait_def: &ItemTrait) -> IdentCollector<'_> {
+    let mut visitor = IdentCollector {
+        ty_names: HashSet::new(),
+        lt_names: HashSet::new(),
+    };
+    visit_item_trait(&mut visitor, trait_def);
+    visitor
 }
 
 fn param_span() -> Span2 {
@@ -1,67 +1,100 @@
 //! Internal attributes of the form `#[auto_impl(name(...))]` that can be
 //! attached to trait items.
 
-use proc_macro2::{Delimiter, TokenTree};
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
 use syn::{
+    parse_quote,
     spanned::Spanned,
-    visit_mut::{visit_item_trait_mut, VisitMut},
-    Attribute, Error, Meta, TraitItem,
+    visit_mut::{visit_item_impl_mut, visit_item_trait_mut, VisitMut},
+    Attribute, Error, Expr, Ident, ImplItem, ItemTrait, Meta, TraitItem,
 };
 
 use crate::proxy::{parse_types, ProxyType};
 
-/// Removes all `#[auto_impl]` attributes that are attached to methods of the
+/// Removes all `#[auto_impl]` attributes that are attached to items of the
 /// given trait.
+///
+/// Our attributes are only allowed on methods. If one is found on another
+/// item, an error is returned, but the attribute is still removed so that the
+/// trait definition can be emitted nonetheless.
 pub(crate) fn remove_our_attrs(trait_def: &mut syn::ItemTrait) -> syn::Result<()> {
-    struct AttrRemover(syn::Result<()>);
-    impl VisitMut for AttrRemover {
-        fn visit_trait_item_mut(&mut self, item: &mut TraitItem) {
-            let item_span = item.span();
-            let (attrs, is_method) = match item {
-                TraitItem::Fn(m) => (&mut m.attrs, true),
-                TraitItem::Const(c) => (&mut c.attrs, false),
-                TraitItem::Type(t) => (&mut t.attrs, false),
-                TraitItem::Macro(m) => (&mut m.attrs, false),
-                _ => {
-                    let err = syn::Error::new(
-                        item.span(),
-                        "encountered unexpected `TraitItem`, cannot handle that, sorry!",
-                    );
-
-                    if let Err(ref mut current_err) = self.0 {
-                        current_err.combine(err);
-                    } else {
-                        self.0 = Err(err);
-                    };
-
-                    return;
-                }
-            };
+    let mut visitor = AttrRemover(Ok(()));
+    visit_item_trait_mut(&mut visitor, trait_def);
+
+    visitor.0
+}
+
+/// Like `remove_our_attrs`, but for the items of an impl block
+/// `impl Trait for Type`.
+pub(crate) fn remove_our_impl_attrs(impl_block: &mut syn::ItemImpl) -> syn::Result<()> {
+    let mut visitor = AttrRemover(Ok(()));
+    visit_item_impl_mut(&mut visitor, impl_block);
+
+    visitor.0
+}
 
-            // Make sure non-methods do not have our attributes.
-            if !is_method && attrs.iter().any(is_our_attr) {
-                let err = syn::Error::new(
-                    item_span,
-                    "`#[auto_impl]` attributes are only allowed on methods",
-                );
+struct AttrRemover(syn::Result<()>);
 
-                if let Err(ref mut current_err) = self.0 {
-                    current_err.combine(err);
-                } else {
-                    self.0 = Err(err);
-                };
+impl AttrRemover {
+    fn add_error(&mut self, err: syn::Error) {
+        if let Err(ref mut current_err) = self.0 {
+            current_err.combine(err);
+        } else {
+            self.0 = Err(err);
+        };
+    }
+
+    fn remove(&mut self, attrs: &mut Vec<Attribute>, is_method: bool, item_span: Span) {
+        // Make sure non-methods do not have our attributes.
+        if !is_method && attrs.iter().any(is_our_attr) {
+            self.add_error(syn::Error::new(
+                item_span,
+                "`#[auto_impl]` attributes are only allowed on methods",
+            ));
+        }
+
+        attrs.retain(|a| !is_our_attr(a));
+    }
+}
 
+impl VisitMut for AttrRemover {
+    fn visit_trait_item_mut(&mut self, item: &mut TraitItem) {
+        let item_span = item.span();
+        let (attrs, is_method) = match item {
+            TraitItem::Fn(m) => (&mut m.attrs, true),
+            TraitItem::Const(c) => (&mut c.attrs, false),
+            TraitItem::Type(t) => (&mut t.attrs, false),
+            TraitItem::Macro(m) => (&mut m.attrs, false),
+            _ => {
+                self.add_error(syn::Error::new(
+                    item.span(),
+                    "encountered unexpected `TraitItem`, cannot handle that, sorry!",
+                ));
                 return;
             }
+        };
 
-            attrs.retain(|a| !is_our_attr(a));
-        }
+        self.remove(attrs, is_method, item_span);
     }
 
-    let mut visitor = AttrRemover(Ok(()));
-    visit_item_trait_mut(&mut visitor, trait_def);
+    fn visit_impl_item_mut(&mut self, item: &mut ImplItem) {
+        let item_span = item.span();
+        let (attrs, is_method) = match item {
+            ImplItem::Fn(m) => (&mut m.attrs, true),
+            ImplItem::Const(c) => (&mut c.attrs, false),
+            ImplItem::Type(t) => (&mut t.attrs, false),
+            ImplItem::Macro(m) => (&mut m.attrs, false),
+            _ => {
+                self.add_error(syn::Error::new(
+                    item.span(),
+                    "encountered unexpected `ImplItem`, cannot handle that, sorry!",
+                ));
+                return;
+            }
+        };
 
-    visitor.0
+        self.remove(attrs, is_method, item_span);
+    }
 }
 
 /// Checks if the given attribute is "our" attribute. That means that it's path
@@ -70,6 +103,51 @@ pub(crate) fn is_our_attr(attr: &Attribute) -> bool {
     attr.path().is_ident("auto_impl")
 }
 
+/// The name of the hidden attribute `#[auto_impl(__listed(...))]` that an
+/// `#[auto_impl]` invocation on a trait leaves for the later ones. It lists
+/// the proxy types of all previous invocations.
+const LISTED: &str = "__listed";
+
+/// Removes the `#[auto_impl(__listed(...))]` attribute from the given trait
+/// and returns the proxy types listed in it.
+pub(crate) fn take_listed_proxies(trait_def: &mut ItemTrait) -> Option<TokenStream> {
+    let (pos, proxies) = trait_def
+        .attrs
+        .iter()
+        .enumerate()
+        .find_map(|(pos, attr)| listed_proxies(attr).map(|proxies| (pos, proxies)))?;
+
+    trait_def.attrs.remove(pos);
+    Some(proxies)
+}
+
+/// Returns the proxy types in the given attribute if it is
+/// `#[auto_impl(__listed(...))]`.
+fn listed_proxies(attr: &Attribute) -> Option<TokenStream> {
+    if !is_our_attr(attr) {
+        return None;
+    }
+    let list = match &attr.meta {
+        Meta::List(list) => list,
+        _ => return None,
+    };
+
+    let mut it = list.tokens.clone().into_iter();
+    match (it.next(), it.next()) {
+        (Some(TokenTree::Ident(name)), Some(TokenTree::Group(group))) if name == LISTED => {
+            Some(group.stream())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the attribute `#[auto_impl(__listed(...))]` with the given proxy
+/// types.
+pub(crate) fn listed_proxies_attr(proxies: TokenStream) -> Attribute {
+    let name = Ident::new(LISTED, Span::call_site());
+    parse_quote!(#[auto_impl(#name(#proxies))])
+}
+
 /// Tries to parse the given attribute as one of our own `auto_impl`
 /// attributes. If it's invalid, an error is emitted and `Err(())` is returned.
 /// You have to make sure that `attr` is one of our attrs with `is_our_attr`
@@ -106,6 +184,43 @@ pub(crate) fn parse_our_attr(attr: &Attribute) -> syn::Result<OurAttr> {
         }
     };
 
+    // `on_none` and `fan_out` have the syntax `name = value`.
+    if name == "on_none" || name == "fan_out" {
+        match it.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == '=' => {}
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    format_args!("expected `{} = <value>`", name),
+                ));
+            }
+        }
+
+        return if name == "on_none" {
+            let expr = syn::parse2::<Expr>(it.collect())?;
+            Ok(OurAttr::OnNone(expr))
+        } else {
+            let reduction = syn::parse2::<Ident>(it.collect())?;
+            let fan_out = match &*reduction.to_string() {
+                "first_some" => FanOut::FirstSome,
+                "all" => FanOut::All,
+                "any" => FanOut::Any,
+                "collect" => FanOut::Collect,
+                _ => {
+                    return Err(Error::new(
+                        reduction.span(),
+                        format_args!(
+                            "invalid reduction `{}`; expected `first_some`, `all`, `any` or \
+                            `collect`",
+                            reduction,
+                        ),
+                    ));
+                }
+            };
+            Ok(OurAttr::FanOut(fan_out))
+        };
+    }
+
     // Extract the parameters (which again, have to be a group delimited by
     // `()`)
     let params = match it.next() {
@@ -132,13 +247,14 @@ pub(crate) fn parse_our_attr(attr: &Attribute) -> syn::Result<OurAttr> {
 
     // Finally match over the name of the attribute.
     let out = if name == "keep_default_for" {
-        let proxy_types = parse_types(params.into());
+        let proxy_types = parse_types(params.into())?;
         OurAttr::KeepDefaultFor(proxy_types)
     } else {
         return Err(Error::new(
             name.span(),
             format_args!(
-                "invalid attribute '{}'; only `keep_default_for` is supported",
+                "invalid attribute '{}'; only `keep_default_for`, `on_none` and `fan_out` are \
+                supported",
                 name
             ),
         ));
@@ -149,7 +265,29 @@ pub(crate) fn parse_our_attr(attr: &Attribute) -> syn::Result<OurAttr> {
 
 /// Attributes of the form `#[auto_impl(...)]` that can be attached to items of
 /// the trait.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone)]
 pub(crate) enum OurAttr {
     KeepDefaultFor(Vec<ProxyType>),
+
+    /// The value returned by the method of the `Option` proxy if it is
+    /// `None`.
+    OnNone(Expr),
+
+    /// How the results of a method are combined by the fan-out proxies
+    /// (`Vec`, `slice` and `array`).
+    FanOut(FanOut),
+}
+
+/// Reductions for the `#[auto_impl(fan_out = ...)]` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FanOut {
+    /// Returns the first `Some` result (`Option<_>`).
+    FirstSome,
+    /// Returns whether all results are `true`.
+    All,
+    /// Returns whether any result is `true`.
+    Any,
+    /// Collects the items of all results into one collection of the return
+    /// type (e.g. `Vec<_>`).
+    Collect,
 }
@@ -0,0 +1,301 @@
+//! Delegation of traits defined in other crates.
+//!
+//! A proc macro only sees the item it is attached to, so `#[derive(Delegate)]`
+//! cannot look at the definition of the trait it should implement. Instead,
+//! `#[auto_impl(export_descriptor)]` emits a `macro_rules!` macro next to the
+//! trait (the descriptor) that replays the trait definition. The derive
+//! invokes the descriptor, which passes the trait to the hidden
+//! `__delegate_impl!` macro that finally generates the impl.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::{
+    parse::{ParseStream, Parser},
+    spanned::Spanned,
+    token, Data, DeriveInput, Error, Fields, Ident, ItemTrait, Member, Path, Token, TraitItem,
+};
+
+use crate::{
+    gen::gen_impls,
+    proxy::{DelegateProxy, EnumProxy, Proxy, ProxyType},
+};
+
+/// The name of the helper attribute of `#[derive(Delegate)]`.
+const ATTR_NAME: &str = "delegate";
+
+/// Returns the name under which the descriptor macro of the trait with the
+/// given name is re-exported next to the trait.
+fn descriptor_name(trait_ident: &Ident) -> Ident {
+    format_ident!("__auto_impl_descriptor_{}", trait_ident)
+}
+
+/// Generates the descriptor macro of the given trait, which passes its input
+/// and the trait definition to `__delegate_impl!`.
+///
+/// `#[macro_export]` puts the macro at the root of the crate, where it is
+/// named after the trait or the name given with
+/// `export_descriptor(name = ...)`. It is re-exported next to the trait (see
+/// `descriptor_name`), so that `#[derive(Delegate)]` finds it by the path of
+/// the trait.
+///
+/// The trait definition still has our attributes on its methods, as
+/// `gen_items` needs them. The bodies of default methods are not needed and
+/// are left out.
+pub(crate) fn gen_descriptor(trait_def: &ItemTrait, name: Option<&Ident>) -> TokenStream2 {
+    let mut trait_def = trait_def.clone();
+    for item in &mut trait_def.items {
+        if let TraitItem::Fn(method) = item {
+            if let Some(block) = &mut method.default {
+                block.stmts.clear();
+            }
+        }
+    }
+
+    // Spanned to the name, so that a clash with another exported trait
+    // points to it.
+    let name = name.unwrap_or(&trait_def.ident);
+    let exported = Ident::new(&format!("__auto_impl_export_{}", name), name.span());
+    let alias = descriptor_name(&trait_def.ident);
+    quote! {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #exported {
+            ($($target:tt)*) => {
+                ::auto_impl::__delegate_impl! { $($target)* #trait_def }
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub use #exported as #alias;
+    }
+}
+
+/// The arguments of `#[delegate(<trait path>, to = <field>)]`.
+struct DelegateAttr {
+    trait_path: Path,
+    to: Option<Member>,
+}
+
+fn parse_delegate_attr(input: ParseStream) -> syn::Result<DelegateAttr> {
+    let trait_path = Path::parse_mod_style(input)?;
+    let mut to = None;
+    if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+        let name = input.parse::<Ident>()?;
+        if name != "to" {
+            return Err(Error::new(
+                name.span(),
+                format_args!("unknown argument `{}`; expected `to = <field>`", name),
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        to = Some(input.parse()?);
+        input.parse::<Option<Token![,]>>()?;
+    }
+
+    Ok(DelegateAttr { trait_path, to })
+}
+
+/// Implements `#[derive(Delegate)]`: invokes the descriptor macro of each
+/// trait listed in a `#[delegate(...)]` attribute.
+pub(crate) fn derive_delegate(input: TokenStream2) -> syn::Result<TokenStream2> {
+    let item = syn::parse2::<DeriveInput>(input.clone())?;
+
+    let mut out = TokenStream2::new();
+    let mut error: Option<Error> = None;
+    let mut any_attr = false;
+    for attr in item.attrs.iter().filter(|a| a.path().is_ident(ATTR_NAME)) {
+        any_attr = true;
+
+        // Check the type here already, so that problems are reported even if
+        // the descriptor macro cannot be found.
+        let args = attr
+            .parse_args_with(parse_delegate_attr)
+            .and_then(|args| proxy_type_for(&item, args.to.as_ref()).map(|_| args));
+        match args {
+            Ok(DelegateAttr { trait_path, to }) => {
+                let descriptor = descriptor_path(&trait_path);
+                out.extend(quote! {
+                    #descriptor! { { #trait_path } { #to } { #input } }
+                });
+            }
+            Err(err) => match &mut error {
+                Some(error) => error.combine(err),
+                None => error = Some(err),
+            },
+        }
+    }
+
+    if !any_attr {
+        return Err(Error::new(
+            item.ident.span(),
+            "`#[derive(Delegate)]` requires at least one `#[delegate(<trait path>)]` attribute",
+        ));
+    }
+    if let Some(error) = error {
+        out.extend(error.into_compile_error());
+    }
+
+    Ok(out)
+}
+
+/// Returns the path of the descriptor macro of the trait with the given path,
+/// which is re-exported next to the trait.
+fn descriptor_path(trait_path: &Path) -> TokenStream2 {
+    let mut path = trait_path.clone();
+    let last = path.segments.last_mut().unwrap();
+    last.ident = descriptor_name(&last.ident);
+    path.into_token_stream()
+}
+
+/// Implements `__delegate_impl!`, which is invoked by the descriptor macros
+/// with the input `{ <trait path> } { <field> } { <type> } <trait definition>`.
+pub(crate) fn delegate_impl(input: TokenStream2) -> syn::Result<TokenStream2> {
+    let parser = |input: ParseStream| {
+        let content;
+        syn::braced!(content in input);
+        let trait_path = Path::parse_mod_style(&content)?;
+
+        let content;
+        syn::braced!(content in input);
+        let to = if content.is_empty() {
+            None
+        } else {
+            Some(content.parse::<Member>()?)
+        };
+
+        let content;
+        let _: token::Brace = syn::braced!(content in input);
+        let item = content.parse::<DeriveInput>()?;
+
+        let trait_def = input.parse::<ItemTrait>()?;
+        Ok((trait_path, to, item, trait_def))
+    };
+    let (trait_path, to, item, trait_def) = parser.parse2(input)?;
+
+    let proxy = Proxy {
+        ty: proxy_type_for(&item, to.as_ref())?,
+        span: trait_path.span(),
+        is_unsafe: false,
+        bounds: Default::default(),
+    };
+    let impls = gen_impls(&[proxy], &trait_def, false);
+
+    // The generated impl refers to the trait by its name, so we import it.
+    let import = if trait_path.segments.len() > 1 {
+        let trait_ident = &trait_def.ident;
+        quote! { use #trait_path as #trait_ident; }
+    } else {
+        TokenStream2::new()
+    };
+
+    Ok(quote! {
+        const _: () = {
+            #import
+            #impls
+        };
+    })
+}
+
+/// Returns the proxy type for the given struct or enum: structs forward to the
+/// given field (or their only field), enums to the single field of each
+/// variant.
+fn proxy_type_for(item: &DeriveInput, to: Option<&Member>) -> syn::Result<ProxyType> {
+    let ident = &item.ident;
+    match &item.data {
+        Data::Struct(data) => {
+            let fields = data.fields.iter().enumerate();
+            let (member, field) = match to {
+                Some(to) => fields
+                    .map(|(i, field)| (member_of(field.ident.as_ref(), i), field))
+                    .find(|(member, _)| member == to)
+                    .ok_or_else(|| {
+                        Error::new(
+                            to.span(),
+                            format_args!(
+                                "struct `{}` has no field `{}`",
+                                ident,
+                                to.to_token_stream()
+                            ),
+                        )
+                    })?,
+                None if data.fields.len() == 1 => {
+                    let field = data.fields.iter().next().unwrap();
+                    (member_of(field.ident.as_ref(), 0), field)
+                }
+                None => {
+                    return Err(Error::new(
+                        ident.span(),
+                        format_args!(
+                            "struct `{}` doesn't have exactly one field, choose the field to \
+                            delegate to with `#[delegate(<trait path>, to = <field>)]`",
+                            ident,
+                        ),
+                    ));
+                }
+            };
+
+            let (_, ty_generics, _) = item.generics.split_for_impl();
+            Ok(ProxyType::Delegate(Box::new(DelegateProxy {
+                ty: syn::parse_quote! { #ident #ty_generics },
+                field: Some(member),
+                field_ty: Some(field.ty.clone()),
+                generics: item.generics.clone(),
+            })))
+        }
+
+        Data::Enum(data) => {
+            if let Some(to) = to {
+                return Err(Error::new(
+                    to.span(),
+                    "`to = <field>` is only supported for structs",
+                ));
+            }
+            if !item.generics.params.is_empty() {
+                return Err(Error::new(
+                    item.generics.span(),
+                    "`#[derive(Delegate)]` doesn't support generic enums",
+                ));
+            }
+            if data.variants.is_empty() {
+                return Err(Error::new(
+                    ident.span(),
+                    format_args!("enum `{}` needs at least one variant", ident),
+                ));
+            }
+
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| match &variant.fields {
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        Ok((variant.ident.clone(), fields.unnamed[0].ty.clone()))
+                    }
+                    _ => Err(Error::new(
+                        variant.span(),
+                        "each variant has to have exactly one unnamed field, e.g. `A(A)`",
+                    )),
+                })
+                .collect::<syn::Result<_>>()?;
+
+            Ok(ProxyType::Enum(Box::new(EnumProxy {
+                ident: ident.clone(),
+                variants,
+                user_defined: true,
+            })))
+        }
+
+        Data::Union(data) => Err(Error::new(
+            data.union_token.span(),
+            "`#[derive(Delegate)]` doesn't support unions",
+        )),
+    }
+}
+
+/// Returns the member to access the field with the given name or index.
+fn member_of(ident: Option<&Ident>, index: usize) -> Member {
+    match ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(index.into()),
+    }
+}
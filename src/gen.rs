@@ -1,58 +1,476 @@
+use std::collections::HashSet;
+
 use proc_macro2::{Span as Span2, TokenStream as TokenStream2, TokenTree as TokenTree2};
 use quote::{ToTokens, TokenStreamExt};
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, Attribute, Error, FnArg, GenericParam, Ident,
-    ItemTrait, Lifetime, Pat, PatIdent, PatType, ReturnType, Signature, Token, TraitBound,
-    TraitBoundModifier, TraitItem, TraitItemConst, TraitItemFn, TraitItemType, Type,
-    TypeParamBound, WherePredicate,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    visit::{visit_path, visit_path_arguments, Visit},
+    Attribute, Error, Expr, ExprPath, FnArg, GenericArgument, GenericParam, Generics, Ident,
+    ImplItem, Index, ItemImpl, ItemTrait, Lifetime, Pat, PatIdent, PatType, PathArguments,
+    ReturnType, Signature, Token, TraitBound, TraitBoundModifier, TraitItem, TraitItemConst,
+    TraitItemFn, TraitItemType, Type, TypeImplTrait, TypeParamBound, TypePath, WherePredicate,
 };
 
 use crate::{
-    analyze::find_suitable_param_names,
-    attr::{is_our_attr, parse_our_attr, OurAttr},
-    proxy::ProxyType,
+    analyze::{find_suitable_param_names, find_tuple_param_names},
+    attr::{is_our_attr, parse_our_attr, FanOut, OurAttr},
+    proxy::{DelegateProxy, EnumProxy, Proxy, ProxyType},
 };
 
 /// Generates one complete impl of the given trait for each of the given proxy
 /// types. All impls are returned as token stream.
+///
+/// Each impl is generated independently: if the trait cannot be implemented for
+/// one proxy type, the impls for the other proxy types are still returned. All
+/// problems with all proxy types are returned as `compile_error!` invocations
+/// in the same token stream. If the trait is incompatible with some of several
+/// given proxy types and `list_compatible` is set, an additional error lists
+/// the proxy types the trait can be implemented for. With a single proxy type,
+/// the error already says what would be allowed.
 pub(crate) fn gen_impls(
-    proxy_types: &[ProxyType],
+    proxies: &[Proxy],
     trait_def: &syn::ItemTrait,
-) -> syn::Result<TokenStream2> {
+    list_compatible: bool,
+) -> TokenStream2 {
     let mut tokens = TokenStream2::new();
+    let mut errors = Vec::new();
+    let mut any_incompatible = false;
+
+    let (proxy_ty_param, proxy_ptr_param, proxy_lt_param) = find_suitable_param_names(trait_def);
 
-    let (proxy_ty_param, proxy_lt_param) = find_suitable_param_names(trait_def);
+    // Invalid attributes on methods are reported only once and not for each
+    // proxy type.
+    if let Err(err) = check_method_attrs(trait_def) {
+        return err.into_compile_error();
+    }
 
     // One impl for each proxy type
-    for proxy_type in proxy_types {
-        let header = gen_header(proxy_type, trait_def, &proxy_ty_param, &proxy_lt_param)?;
-        let items = gen_items(proxy_type, trait_def, &proxy_ty_param)?;
+    for proxy in proxies {
+        let proxy_type = &proxy.ty;
+
+        // Enums are generated by us. They are emitted even if the trait
+        // cannot be implemented for them, so that code using them doesn't
+        // lead to more errors.
+        if let ProxyType::Enum(e) = proxy_type {
+            if e.variants.is_empty() {
+                errors.push(Error::new(
+                    proxy.span,
+                    format_args!(
+                        "the enum `{}` needs at least one variant, e.g. `enum {}(A, B)`",
+                        e.ident, e.ident,
+                    ),
+                ));
+                continue;
+            }
+            if !e.user_defined {
+                tokens.append_all(gen_enum_def(e, proxy.span, trait_def));
+            }
+            tokens.append_all(gen_enum_assoc_type_checks(e, trait_def));
+        }
+        if let ProxyType::Delegate(d) = proxy_type {
+            if d.field.is_none() {
+                let ty = d.ty.to_token_stream();
+                errors.push(Error::new(
+                    proxy.span,
+                    format_args!(
+                        "missing the field to delegate to, e.g. `delegate({} => self.inner)`",
+                        ty,
+                    ),
+                ));
+                continue;
+            }
+        }
+
+        if let Err(err) = proxy.bounds.check_placeholder(&trait_def.generics) {
+            errors.push(err);
+            continue;
+        }
 
-        if let ProxyType::Box | ProxyType::Rc | ProxyType::Arc = proxy_type {
+        // Forwarding the methods of an `unsafe trait` is only allowed if the
+        // user explicitly listed the proxy type in `unsafe(...)`.
+        let unsafety_check = check_unsafety(proxy, trait_def);
+        let unsafety = trait_def.unsafety;
+
+        let header = gen_header(
+            proxy_type,
+            proxy.span,
+            &proxy.bounds.predicates(&proxy_ty_param),
+            trait_def,
+            &proxy_ty_param,
+            &proxy_ptr_param,
+            &proxy_lt_param,
+        );
+        let items = gen_items(proxy_type, trait_def, &proxy_ty_param, None);
+
+        let (header, items) = match (unsafety_check, header, items) {
+            (Ok(()), Ok(header), Ok(items)) => (header, items),
+            (unsafety_check, header, items) => {
+                any_incompatible |= header.is_err() || items.is_err();
+                errors.extend(unsafety_check.err());
+                // For Fn-traits, the header already reports what's wrong with
+                // the items of the trait.
+                let header_failed = header.is_err();
+                errors.extend(header.err());
+                if !(header_failed && proxy_type.is_fn()) {
+                    errors.extend(items.err());
+                }
+                continue;
+            }
+        };
+
+        if let ProxyType::Box | ProxyType::Rc | ProxyType::Arc | ProxyType::Cow | ProxyType::Vec =
+            proxy_type
+        {
             tokens.append_all(quote! {
                 const _: () = {
                     extern crate alloc;
-                    #header { #( #items )* }
+                    #unsafety #header { #( #items )* }
                 };
             });
         } else {
             tokens.append_all(quote! {
                 const _: () = {
-                    #header { #( #items )* }
+                    #unsafety #header { #( #items )* }
                 };
             });
         }
     }
 
-    Ok(tokens)
+    // Tell the user which proxy types would work instead.
+    if any_incompatible && list_compatible && proxies.len() > 1 {
+        let compatible = ProxyType::ALL
+            .iter()
+            .filter(|proxy_type| is_compatible(proxy_type, trait_def))
+            .map(|proxy_type| format!("`{}`", proxy_type.name()))
+            .collect::<Vec<_>>();
+
+        let msg = if compatible.is_empty() {
+            format!(
+                "the trait `{}` cannot be auto-implemented for any proxy type",
+                trait_def.ident,
+            )
+        } else {
+            format!(
+                "the trait `{}` can only be auto-implemented for {}",
+                trait_def.ident,
+                compatible.join(", "),
+            )
+        };
+        errors.push(Error::new(Span2::call_site(), msg));
+    }
+
+    if let Some(err) = combine_errors(errors) {
+        tokens.append_all(err.into_compile_error());
+    }
+
+    tokens
+}
+
+/// Returns whether the given trait can be auto-implemented for the given
+/// proxy type, ignoring the `unsafe` opt-in. For fan-out proxies, the
+/// arguments of the forwarded methods have to be known to be `Copy`.
+pub(crate) fn is_compatible(proxy_type: &ProxyType, trait_def: &ItemTrait) -> bool {
+    if proxy_type.is_fan_out() && !fan_out_args_known_copy(proxy_type, trait_def) {
+        return false;
+    }
+
+    let (proxy_ty_param, proxy_ptr_param, proxy_lt_param) = find_suitable_param_names(trait_def);
+    gen_header(
+        proxy_type,
+        Span2::call_site(),
+        &[],
+        trait_def,
+        &proxy_ty_param,
+        &proxy_ptr_param,
+        &proxy_lt_param,
+    )
+    .is_ok()
+        && gen_items(proxy_type, trait_def, &proxy_ty_param, None).is_ok()
+}
+
+/// Checks if all arguments of the methods the given fan-out proxy type calls
+/// for each element are obviously `Copy`. Other types might be `Copy` as
+/// well, but we cannot know that.
+fn fan_out_args_known_copy(proxy_type: &ProxyType, trait_def: &ItemTrait) -> bool {
+    fn is_known_copy(ty: &Type) -> bool {
+        const PRIMITIVES: &[&str] = &[
+            "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+            "i128", "isize", "f32", "f64",
+        ];
+
+        match ty {
+            Type::Reference(r) => r.mutability.is_none(),
+            Type::Ptr(_) | Type::BareFn(_) | Type::Never(_) => true,
+            Type::Tuple(t) => t.elems.iter().all(is_known_copy),
+            Type::Array(a) => is_known_copy(&a.elem),
+            Type::Paren(p) => is_known_copy(&p.elem),
+            Type::Path(p) => {
+                p.qself.is_none() && PRIMITIVES.iter().any(|prim| p.path.is_ident(prim))
+            }
+            _ => false,
+        }
+    }
+
+    trait_def.items.iter().all(|item| match item {
+        TraitItem::Fn(m) => {
+            m.sig.receiver().is_none()
+                || should_keep_default_for(m, proxy_type).unwrap_or(false)
+                || m.sig.inputs.iter().all(|arg| match arg {
+                    FnArg::Typed(arg) => is_known_copy(&arg.ty),
+                    FnArg::Receiver(_) => true,
+                })
+        }
+        _ => true,
+    })
+}
+
+/// Generates the impls for the proxy types listed in an `#[auto_impl]`
+/// attribute on an impl block `impl Trait for Type`. The definition of the
+/// trait is unknown, so the items of the impl block are used in its place.
+/// The generated impls forward to the impl block (e.g.
+/// `impl<'a> Trait for &'a Type`).
+pub(crate) fn gen_impl_block_impls(proxies: &[Proxy], impl_block: &ItemImpl) -> TokenStream2 {
+    let trait_def = match trait_def_from_impl(impl_block) {
+        Ok(trait_def) => trait_def,
+        Err(err) => return err.into_compile_error(),
+    };
+    if let Err(err) = check_method_attrs(&trait_def) {
+        return err.into_compile_error();
+    }
+
+    let (proxy_ty_param, _, proxy_lt_param) = find_suitable_param_names(&trait_def);
+    let self_ty = &impl_block.self_ty;
+    let trait_path = &impl_block.trait_.as_ref().unwrap().1;
+    let target = quote! { <#self_ty as #trait_path> };
+    let params = &impl_block.generics.params;
+    let where_clause = &impl_block.generics.where_clause;
+
+    let mut tokens = TokenStream2::new();
+    let mut errors = Vec::new();
+    for proxy in proxies {
+        let proxy_type = &proxy.ty;
+        let proxy_span = proxy.span;
+
+        // There is no type parameter the placeholder of extra bounds could
+        // refer to.
+        if !proxy.bounds.is_empty() {
+            errors.push(Error::new(
+                proxy_span,
+                "`where` bounds are not supported on impl blocks",
+            ));
+            continue;
+        }
+
+        // Only these proxy types can be local if the trait is not (`&`,
+        // `&mut` and `Box` are `#[fundamental]`).
+        #[rustfmt::skip]
+        let (lt_param, proxy_self_ty) = match proxy_type {
+            ProxyType::Ref => (Some(&proxy_lt_param), quote_spanned! {proxy_span=> & #proxy_lt_param #self_ty }),
+            ProxyType::RefMut => (Some(&proxy_lt_param), quote_spanned! {proxy_span=> & #proxy_lt_param mut #self_ty }),
+            ProxyType::Box => (None, quote_spanned! {proxy_span=> alloc::boxed::Box<#self_ty> }),
+            ProxyType::Rc => (None, quote_spanned! {proxy_span=> alloc::rc::Rc<#self_ty> }),
+            ProxyType::Arc => (None, quote_spanned! {proxy_span=> alloc::sync::Arc<#self_ty> }),
+            _ => {
+                errors.push(Error::new(
+                    proxy_span,
+                    format_args!(
+                        "`{}` is not supported on impl blocks (only `&`, `&mut`, `Box`, `Rc` \
+                        and `Arc` are)",
+                        proxy_type.name(),
+                    ),
+                ));
+                continue;
+            }
+        };
+
+        let unsafety_check = check_unsafety(proxy, &trait_def);
+        let items = gen_items(proxy_type, &trait_def, &proxy_ty_param, Some(&target));
+        let items = match (unsafety_check, items) {
+            (Ok(()), Ok(items)) => items,
+            (unsafety_check, items) => {
+                errors.extend(unsafety_check.err());
+                errors.extend(items.err());
+                continue;
+            }
+        };
+
+        let unsafety = impl_block.unsafety;
+        let lt_param = lt_param.map(|lt| quote_spanned! {proxy_span=> #lt, });
+        let alloc = lt_param
+            .is_none()
+            .then(|| quote_spanned! {proxy_span=> extern crate alloc; });
+        tokens.append_all(quote_spanned! {proxy_span=>
+            const _: () = {
+                #alloc
+                #unsafety impl<#lt_param #params> #trait_path for #proxy_self_ty #where_clause {
+                    #( #items )*
+                }
+            };
+        });
+    }
+
+    if let Some(err) = combine_errors(errors) {
+        tokens.append_all(err.into_compile_error());
+    }
+
+    tokens
+}
+
+/// Builds a trait definition from the items of the given impl block
+/// `impl Trait for Type`, so that it can be passed to `gen_items`. Methods
+/// keep their bodies as default, which is only used to check
+/// `keep_default_for`. The generics of the trait are those of the impl
+/// block, so that our parameter names don't collide with them.
+fn trait_def_from_impl(impl_block: &ItemImpl) -> syn::Result<ItemTrait> {
+    let trait_path = match &impl_block.trait_ {
+        Some((None, path, _)) => path,
+        Some((Some(bang), _, _)) => {
+            return Err(Error::new(
+                bang.span(),
+                "`#[auto_impl]` cannot be used on negative impls",
+            ));
+        }
+        None => {
+            return Err(Error::new(
+                impl_block.impl_token.span(),
+                "`#[auto_impl]` can only be used on trait definitions and impl blocks of traits \
+                (`impl Trait for Type`)",
+            ));
+        }
+    };
+
+    let items = impl_block.items.iter().map(|item| match item {
+        ImplItem::Fn(f) => {
+            let (attrs, sig, block) = (&f.attrs, &f.sig, &f.block);
+            quote! { #(#attrs)* #sig #block }
+        }
+        ImplItem::Const(c) => {
+            let (attrs, ident, ty) = (&c.attrs, &c.ident, &c.ty);
+            quote! { #(#attrs)* const #ident: #ty; }
+        }
+        ImplItem::Type(t) => {
+            let (attrs, ident, generics) = (&t.attrs, &t.ident, &t.generics);
+            let where_clause = &generics.where_clause;
+            quote! { #(#attrs)* type #ident #generics #where_clause; }
+        }
+        // Rejected by `gen_items`
+        other => other.to_token_stream(),
+    });
+
+    let unsafety = impl_block.unsafety;
+    let ident = &trait_path.segments.last().unwrap().ident;
+    let generics = &impl_block.generics;
+    let where_clause = &generics.where_clause;
+    syn::parse2(quote! {
+        #unsafety trait #ident #generics #where_clause {
+            #( #items )*
+        }
+    })
+}
+
+/// Checks the `#[auto_impl(...)]` attributes on all methods of the trait.
+fn check_method_attrs(trait_def: &ItemTrait) -> syn::Result<()> {
+    let attr_errors = trait_def.items.iter().filter_map(|item| match item {
+        TraitItem::Fn(m) => keep_default_for_types(m)
+            .and_then(|_| on_none_expr(m))
+            .and_then(|_| fan_out_reduction(m))
+            .err(),
+        _ => None,
+    });
+
+    match combine_errors(attr_errors) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Combines all given errors into one, so that all of them are reported.
+/// Returns `None` if there are no errors.
+fn combine_errors(errors: impl IntoIterator<Item = Error>) -> Option<Error> {
+    errors.into_iter().reduce(|mut combined, err| {
+        combined.combine(err);
+        combined
+    })
+}
+
+/// Checks that the proxy was listed in `unsafe(...)` if and only if the trait
+/// is an `unsafe trait`.
+fn check_unsafety(proxy: &Proxy, trait_def: &ItemTrait) -> syn::Result<()> {
+    let trait_name = &trait_def.ident;
+    let proxy_name = proxy.ty.name();
+
+    match (trait_def.unsafety, proxy.is_unsafe) {
+        (Some(unsafety), false) => {
+            let msg = format!(
+                "the trait `{}` is an `unsafe trait` and cannot be auto-implemented for \
+                    `{}` without an explicit opt-in. Implementing it for a proxy type asserts \
+                    that the proxy type upholds the safety contract of the trait whenever the \
+                    proxied type does. If this is the case, list the proxy type in \
+                    `unsafe(...)`, e.g. `#[auto_impl(unsafe({}))]`",
+                trait_name, proxy_name, proxy_name,
+            );
+            Err(Error::new(unsafety.span(), msg))
+        }
+        (None, true) => {
+            let msg = format!(
+                "`{}` is listed in `unsafe(...)`, but the trait `{}` is not an `unsafe trait` \
+                    (the `unsafe(...)` opt-in is only needed for unsafe traits)",
+                proxy_name, trait_name,
+            );
+            Err(Error::new(trait_name.span(), msg))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Generates the definition of the enum of the given enum proxy type, which
+/// has the same visibility as the trait, and a `From` impl for each variant.
+fn gen_enum_def(e: &EnumProxy, proxy_span: Span2, trait_def: &ItemTrait) -> TokenStream2 {
+    let vis = &trait_def.vis;
+    let ident = &e.ident;
+    let names = e.variants.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let tys = e.variants.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+
+    let doc = format!(
+        "One of several implementations of [`{}`], generated by `auto_impl`.",
+        trait_def.ident,
+    );
+    let variant_docs = tys
+        .iter()
+        .map(|ty| format!("A `{}`.", ty.to_token_stream()))
+        .collect::<Vec<_>>();
+
+    quote_spanned! {proxy_span=>
+        #[doc = #doc]
+        #vis enum #ident {
+            #(
+                #[doc = #variant_docs]
+                #names(#tys),
+            )*
+        }
+
+        #(
+            impl ::core::convert::From<#tys> for #ident {
+                fn from(inner: #tys) -> Self {
+                    #ident::#names(inner)
+                }
+            }
+        )*
+    }
 }
 
 /// Generates the header of the impl of the given trait for the given proxy
-/// type.
+/// type. The generated tokens are spanned to `proxy_span`, the proxy type in
+/// the attribute, so that errors in the impl point to it. `extra_bounds` are
+/// appended to the where clause.
 fn gen_header(
     proxy_type: &ProxyType,
+    proxy_span: Span2,
+    extra_bounds: &[WherePredicate],
     trait_def: &ItemTrait,
     proxy_ty_param: &Ident,
+    proxy_ptr_param: &Ident,
     proxy_lt_param: &Lifetime,
 ) -> syn::Result<TokenStream2> {
     // Generate generics for impl positions from trait generics.
@@ -60,7 +478,27 @@ fn gen_header(
 
     // The name of the trait with all generic parameters applied.
     let trait_ident = &trait_def.ident;
-    let trait_path = quote! { #trait_ident #trait_generics };
+    let trait_path = quote_spanned! {proxy_span=> #trait_ident #trait_generics };
+
+    // Check which pinned receivers are used by the methods we generate. Those
+    // need additional bounds: `Pin<P>` needs mutable access to the pointee for
+    // `self: Pin<&mut Self>` receivers and all other proxy types need the
+    // pointee to be `Unpin` to create a pinned reference to it.
+    let mut pin_ref_used = false;
+    let mut pin_mut_used = false;
+    for item in &trait_def.items {
+        if let TraitItem::Fn(m) = item {
+            if should_keep_default_for(m, proxy_type)? {
+                continue;
+            }
+
+            match SelfType::from_sig(&m.sig) {
+                SelfType::PinRef => pin_ref_used = true,
+                SelfType::PinMut => pin_mut_used = true,
+                _ => {}
+            }
+        }
+    }
 
     // Here we assemble the parameter list of the impl (the thing in
     // `impl< ... >`). This is simply the parameter list of the trait with
@@ -127,38 +565,37 @@ fn gen_header(
 
             // Check if the first parameter is `self` by value. In that
             // case, we might require `Self` to be `Sized`.
-            let self_value_param = match m.sig.inputs.first() {
-                Some(FnArg::Receiver(receiver)) => receiver.reference.is_none(),
-                _ => false,
-            };
+            let self_value_param = SelfType::from_sig(&m.sig) == SelfType::Value;
 
-            // Check if return type is `Self`
-            let self_value_return = match &m.sig.output {
-                ReturnType::Type(_, t) => {
-                    if let Type::Path(p) = &**t {
-                        p.path.is_ident("Self")
-                    } else {
-                        false
-                    }
-                }
-                _ => false,
-            };
+            // Check if return type is `Self` (possibly wrapped in `Option` or
+            // `Result`)
+            let self_value_return = matches!(
+                SelfReturn::from_sig(&m.sig),
+                SelfReturn::Value | SelfReturn::Option | SelfReturn::Result
+            );
 
-            // TODO: check for `Self` parameter in any other argument.
+            // Check if `Self` is passed by value in any other argument
+            let self_value_arg = m.sig.inputs.iter().any(|arg| match arg {
+                FnArg::Typed(arg) => matches!(
+                    SelfArg::from_type(&arg.ty),
+                    SelfArg::Value | SelfArg::OptionValue
+                ),
+                FnArg::Receiver(_) => false,
+            });
 
             // If for this method, `Self` is used in a position that
             // requires `Self: Sized` or this bound is added explicitly, we
             // cannot add the `?Sized` relaxation to the impl body.
-            if self_value_param || self_value_return || self_is_bounded_sized {
+            if self_value_param || self_value_arg || self_value_return || self_is_bounded_sized {
                 sized_required = true;
                 break;
             }
         }
 
         let relaxation = if sized_required {
-            quote! {}
+            quote_spanned! {proxy_span=>}
         } else {
-            quote! { + ?::core::marker::Sized }
+            quote_spanned! {proxy_span=> + ?::core::marker::Sized }
         };
 
         // Check if there are some `Self: Foo` bounds on methods. If so, we
@@ -221,19 +658,76 @@ fn gen_header(
             );
         }
 
-        // Determine if our proxy type needs a lifetime parameter
+        // Pinned receivers can only be forwarded through references and smart
+        // pointers if the pointee can be pinned safely (`Pin<P>` itself
+        // doesn't need this).
+        let unpin = if pin_ref_used || pin_mut_used {
+            quote_spanned! {proxy_span=> + ::core::marker::Unpin }
+        } else {
+            quote_spanned! {proxy_span=>}
+        };
+
+        // Determine if our proxy type needs a lifetime or pointer parameter
         let (mut params, ty_bounds) = match proxy_type {
             ProxyType::Ref | ProxyType::RefMut => (
-                quote! { #proxy_lt_param, },
-                quote! { : #proxy_lt_param + #trait_path #relaxation #(+ #additional_bounds)* },
+                quote_spanned! {proxy_span=> #proxy_lt_param, },
+                quote_spanned! {proxy_span=> : #proxy_lt_param + #trait_path #relaxation #(+ #additional_bounds)* #unpin },
+            ),
+            ProxyType::Box | ProxyType::Rc | ProxyType::Arc | ProxyType::ManuallyDrop => (
+                quote_spanned! {proxy_span=>},
+                quote_spanned! {proxy_span=> : #trait_path #relaxation #(+ #additional_bounds)* #unpin },
+            ),
+            // These wrappers require their content to be sized.
+            ProxyType::AssertUnwindSafe
+            | ProxyType::Wrapping
+            | ProxyType::Reverse
+            | ProxyType::Option
+            | ProxyType::Vec
+            | ProxyType::Slice
+            | ProxyType::Array
+            | ProxyType::Tuple(_) => (
+                quote_spanned! {proxy_span=>},
+                quote_spanned! {proxy_span=> : #trait_path #(+ #additional_bounds)* #unpin },
+            ),
+            ProxyType::Cow => (
+                quote_spanned! {proxy_span=> #proxy_lt_param, },
+                quote_spanned! {proxy_span=>
+                    : #proxy_lt_param + alloc::borrow::ToOwned + #trait_path #relaxation
+                        #(+ #additional_bounds)* #unpin
+                },
             ),
-            ProxyType::Box | ProxyType::Rc | ProxyType::Arc => (
-                quote! {},
-                quote! { : #trait_path #relaxation #(+ #additional_bounds)* },
+            ProxyType::Pin => (
+                quote_spanned! {proxy_span=> #proxy_ptr_param, },
+                quote_spanned! {proxy_span=> : #trait_path #relaxation #(+ #additional_bounds)* },
             ),
+            // We cannot know whether the custom pointer supports unsized
+            // types, so we never add the `?Sized` relaxation.
+            ProxyType::Custom(custom) => {
+                let lifetimes = custom.lifetimes();
+                (
+                    quote_spanned! {proxy_span=> #(#lifetimes,)* },
+                    quote_spanned! {proxy_span=> : #trait_path #(+ #additional_bounds)* #unpin },
+                )
+            }
+            // The enum is not generic, the bounds for its variants are added
+            // to the where clause.
+            ProxyType::Enum(_) => (quote_spanned! {proxy_span=>}, quote_spanned! {proxy_span=>}),
+            // The struct isn't generic either, but it can have lifetimes. The
+            // bound for its field is added to the where clause (if we know
+            // the type of the field).
+            ProxyType::Delegate(d) => {
+                let lifetimes = d.lifetimes();
+                (
+                    quote_spanned! {proxy_span=> #(#lifetimes,)* },
+                    quote_spanned! {proxy_span=>},
+                )
+            }
             ProxyType::Fn | ProxyType::FnMut | ProxyType::FnOnce => {
                 let fn_bound = gen_fn_type_for_trait(proxy_type, trait_def)?;
-                (quote! {}, quote! { : #fn_bound })
+                (
+                    quote_spanned! {proxy_span=>},
+                    quote_spanned! {proxy_span=> : #fn_bound },
+                )
             }
         };
 
@@ -265,11 +759,48 @@ fn gen_header(
         // Append proxy type parameter (if there aren't any parameters so far,
         // we need to add a comma first).
         let comma = if params.is_empty() || tts.is_empty() {
-            quote! {}
+            quote_spanned! {proxy_span=>}
         } else {
-            quote! { , }
+            quote_spanned! {proxy_span=> , }
         };
-        params.append_all(quote! { #comma #proxy_ty_param #ty_bounds });
+        // Tuples have one type parameter per element instead, enums and
+        // delegating structs none.
+        let ty_params = match proxy_type {
+            ProxyType::Tuple(arity) => find_tuple_param_names(trait_def, proxy_ty_param, *arity),
+            ProxyType::Enum(_) | ProxyType::Delegate(_) => Vec::new(),
+            _ => vec![proxy_ty_param.clone()],
+        };
+        params.append_all(quote_spanned! {proxy_span=> #comma #(#ty_params #ty_bounds),* });
+
+        // The length of arrays is a const parameter. It has to come last, as
+        // lifetime parameters of the trait have to precede it.
+        if *proxy_type == ProxyType::Array {
+            params.append_all(quote_spanned! {proxy_span=> , const #proxy_ptr_param: usize });
+        }
+
+        // The type and const parameters of a struct using
+        // `#[derive(Delegate)]` come last as well (its lifetimes were added
+        // above). Their bounds are added to the where clause.
+        if let ProxyType::Delegate(d) = proxy_type {
+            for param in &d.generics.params {
+                let param = match param {
+                    GenericParam::Type(ty) => ty.ident.to_token_stream(),
+                    GenericParam::Const(c) => {
+                        let (ident, ty) = (&c.ident, &c.ty);
+                        quote_spanned! {proxy_span=> const #ident: #ty }
+                    }
+                    GenericParam::Lifetime(_) => continue,
+                };
+                let ends_with_comma = matches!(
+                    params.clone().into_iter().last(),
+                    Some(TokenTree2::Punct(p)) if p.as_char() == ','
+                );
+                if !params.is_empty() && !ends_with_comma {
+                    params.append_all(quote_spanned! {proxy_span=> , });
+                }
+                params.append_all(param);
+            }
+        }
 
         params
     };
@@ -278,25 +809,102 @@ fn gen_header(
     // implemented for).
     #[rustfmt::skip]
     let self_ty = match *proxy_type {
-        ProxyType::Ref      => quote! { & #proxy_lt_param #proxy_ty_param },
-        ProxyType::RefMut   => quote! { & #proxy_lt_param mut #proxy_ty_param },
-        ProxyType::Arc      => quote! { alloc::sync::Arc<#proxy_ty_param> },
-        ProxyType::Rc       => quote! { alloc::rc::Rc<#proxy_ty_param> },
-        ProxyType::Box      => quote! { alloc::boxed::Box<#proxy_ty_param> },
-        ProxyType::Fn       => quote! { #proxy_ty_param },
-        ProxyType::FnMut    => quote! { #proxy_ty_param },
-        ProxyType::FnOnce   => quote! { #proxy_ty_param },
+        ProxyType::Ref      => quote_spanned! {proxy_span=> & #proxy_lt_param #proxy_ty_param },
+        ProxyType::RefMut   => quote_spanned! {proxy_span=> & #proxy_lt_param mut #proxy_ty_param },
+        ProxyType::Arc      => quote_spanned! {proxy_span=> alloc::sync::Arc<#proxy_ty_param> },
+        ProxyType::Rc       => quote_spanned! {proxy_span=> alloc::rc::Rc<#proxy_ty_param> },
+        ProxyType::Box      => quote_spanned! {proxy_span=> alloc::boxed::Box<#proxy_ty_param> },
+        ProxyType::Cow      => quote_spanned! {proxy_span=> alloc::borrow::Cow<#proxy_lt_param, #proxy_ty_param> },
+        ProxyType::Pin      => quote_spanned! {proxy_span=> ::core::pin::Pin<#proxy_ptr_param> },
+        ProxyType::ManuallyDrop => quote_spanned! {proxy_span=> ::core::mem::ManuallyDrop<#proxy_ty_param> },
+        ProxyType::AssertUnwindSafe => quote_spanned! {proxy_span=> ::core::panic::AssertUnwindSafe<#proxy_ty_param> },
+        ProxyType::Wrapping => quote_spanned! {proxy_span=> ::core::num::Wrapping<#proxy_ty_param> },
+        ProxyType::Reverse  => quote_spanned! {proxy_span=> ::core::cmp::Reverse<#proxy_ty_param> },
+        ProxyType::Option   => quote_spanned! {proxy_span=> ::core::option::Option<#proxy_ty_param> },
+        ProxyType::Vec      => quote_spanned! {proxy_span=> alloc::vec::Vec<#proxy_ty_param> },
+        ProxyType::Slice    => quote_spanned! {proxy_span=> [#proxy_ty_param] },
+        ProxyType::Array    => quote_spanned! {proxy_span=> [#proxy_ty_param; #proxy_ptr_param] },
+        ProxyType::Tuple(arity) => {
+            let ty_params = find_tuple_param_names(trait_def, proxy_ty_param, arity);
+            quote_spanned! {proxy_span=> ( #(#ty_params,)* ) }
+        }
+        ProxyType::Fn       => quote_spanned! {proxy_span=> #proxy_ty_param },
+        ProxyType::FnMut    => quote_spanned! {proxy_span=> #proxy_ty_param },
+        ProxyType::FnOnce   => quote_spanned! {proxy_span=> #proxy_ty_param },
+        ProxyType::Custom(ref custom) => custom.self_ty(proxy_ty_param).into_token_stream(),
+        ProxyType::Enum(ref e) => e.ident.to_token_stream(),
+        ProxyType::Delegate(ref d) => d.ty.to_token_stream(),
     };
 
     // If the trait has super traits, we need to add the super trait bound to
-    // our self type. This can only be done in the where clause, so we need to
-    // combine the existing where clauses with our new predicate in that case.
-    let where_clause = if !trait_def.supertraits.is_empty() {
-        let mut out = quote! { where };
+    // our self type. Similarly, the pointer inside `Pin<P>` and custom
+    // pointers have to point to our type parameter. This can only be done in
+    // the where clause, so we need to combine the existing where clauses with
+    // our new predicates in that case.
+    let custom_deref = matches!(proxy_type, ProxyType::Custom(custom) if custom.deref);
+    let is_enum = matches!(proxy_type, ProxyType::Enum(_));
+    let delegate = match proxy_type {
+        ProxyType::Delegate(d) => Some(d),
+        _ => None,
+    };
+    let where_clause = if !trait_def.supertraits.is_empty()
+        || *proxy_type == ProxyType::Pin
+        || custom_deref
+        || is_enum
+        || delegate.is_some()
+        || !extra_bounds.is_empty()
+    {
+        let mut out = quote_spanned! {proxy_span=> where };
 
         if !trait_def.supertraits.is_empty() {
-            let supertraits = &trait_def.supertraits;
-            out.extend(quote! { #self_ty: #supertraits, });
+            // Each bound is spanned to the supertrait in the trait definition,
+            // so that an unsatisfied bound points to the supertrait.
+            for bound in &trait_def.supertraits {
+                out.extend(quote_spanned! {bound.span()=> #self_ty: #bound, });
+            }
+        }
+        if *proxy_type == ProxyType::Pin {
+            if pin_mut_used {
+                out.extend(quote_spanned! {proxy_span=>
+                    #proxy_ptr_param: ::core::ops::DerefMut<Target = #proxy_ty_param>
+                        + ::core::marker::Unpin,
+                });
+            } else {
+                out.extend(quote_spanned! {proxy_span=>
+                    #proxy_ptr_param: ::core::ops::Deref<Target = #proxy_ty_param>,
+                });
+            }
+        }
+        if let ProxyType::Custom(custom) = proxy_type {
+            if custom.deref_mut {
+                out.extend(quote_spanned! {proxy_span=>
+                    #self_ty: ::core::ops::DerefMut<Target = #proxy_ty_param>,
+                });
+            } else if custom.deref {
+                out.extend(quote_spanned! {proxy_span=>
+                    #self_ty: ::core::ops::Deref<Target = #proxy_ty_param>,
+                });
+            }
+        }
+        if let ProxyType::Enum(e) = proxy_type {
+            out.extend(gen_enum_predicates(e, trait_def, &trait_path));
+        }
+        if let Some(d) = delegate {
+            if let Some(field_ty) = &d.field_ty {
+                out.extend(quote_spanned! {field_ty.span()=> #field_ty: #trait_path, });
+
+                // Pinned receivers pin the field again, which is only
+                // possible if it's `Unpin`.
+                if pin_ref_used || pin_mut_used {
+                    out.extend(quote_spanned! {field_ty.span()=>
+                        #field_ty: ::core::marker::Unpin,
+                    });
+                }
+            }
+            out.extend(gen_generics_predicates(&d.generics));
+        }
+        for predicate in extra_bounds {
+            out.extend(quote_spanned! {predicate.span()=> #predicate, });
         }
         if let Some(predicates) = where_clause.map(|c| &c.predicates) {
             out.extend(predicates.into_token_stream());
@@ -308,11 +916,138 @@ fn gen_header(
     };
 
     // Combine everything
-    Ok(quote! {
+    Ok(quote_spanned! {proxy_span=>
         impl<#impl_generics> #trait_path for #self_ty #where_clause
     })
 }
 
+/// Generates the where predicates for the impl for an enum: all variants have
+/// to implement the trait, and their associated types have to be the same as
+/// those of the first variant. Each predicate is spanned to the type of the
+/// variant in the attribute.
+///
+/// If the trait has no generic parameters, the predicates about associated
+/// types would only mention concrete types and make the impl fail with an
+/// unhelpful error. The associated types are checked by
+/// `gen_enum_assoc_type_checks` in that case.
+fn gen_enum_predicates(
+    e: &EnumProxy,
+    trait_def: &ItemTrait,
+    trait_path: &TokenStream2,
+) -> TokenStream2 {
+    let trait_ident = &trait_def.ident;
+    let trait_args = trait_def.generics.params.iter().map(|param| match param {
+        GenericParam::Lifetime(lt) => lt.lifetime.to_token_stream(),
+        GenericParam::Type(ty) => ty.ident.to_token_stream(),
+        GenericParam::Const(c) => c.ident.to_token_stream(),
+    });
+    let trait_args = trait_args.collect::<Vec<_>>();
+
+    // Generic associated types cannot be compared like this. Mismatches of
+    // those lead to type errors in the methods instead.
+    let assoc_types = trait_def
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Type(ty) if ty.generics.params.is_empty() => Some(&ty.ident),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let first = &e.variants[0].1;
+    let mut out = TokenStream2::new();
+    for (i, (_, ty)) in e.variants.iter().enumerate() {
+        if i == 0 || assoc_types.is_empty() || trait_args.is_empty() {
+            out.extend(quote_spanned! {ty.span()=> #ty: #trait_path, });
+        } else {
+            out.extend(quote_spanned! {ty.span()=>
+                #ty: #trait_ident<
+                    #(#trait_args,)*
+                    #(#assoc_types = <#first as #trait_path>::#assoc_types),*
+                >,
+            });
+        }
+    }
+
+    out
+}
+
+/// Generates checks that the associated types of all variants of the enum
+/// are the same as those of the first variant, for traits without generic
+/// parameters (see `gen_enum_predicates`). A mismatch leads to an error about
+/// the helper trait `AllVariantsMustHaveTheSameAssociatedTypes`, spanned to
+/// the type of the variant in the attribute.
+fn gen_enum_assoc_type_checks(e: &EnumProxy, trait_def: &ItemTrait) -> TokenStream2 {
+    if !trait_def.generics.params.is_empty() {
+        return TokenStream2::new();
+    }
+
+    let trait_ident = &trait_def.ident;
+    let first = &e.variants[0].1;
+    let checks = trait_def
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Type(ty) if ty.generics.params.is_empty() => Some(&ty.ident),
+            _ => None,
+        })
+        .flat_map(|assoc| {
+            e.variants[1..].iter().map(move |(_, ty)| {
+                // Span the whole call to the variant, so that the error
+                // doesn't also cover the associated type in the trait.
+                let assoc = Ident::new(&assoc.to_string(), ty.span());
+                quote_spanned! {ty.span()=>
+                    assert_same::<
+                        <#ty as #trait_ident>::#assoc,
+                        <#first as #trait_ident>::#assoc,
+                    >();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    if checks.is_empty() {
+        return TokenStream2::new();
+    }
+
+    quote! {
+        const _: fn() = || {
+            trait AllVariantsMustHaveTheSameAssociatedTypes<T: ?::core::marker::Sized> {}
+            impl<T: ?::core::marker::Sized> AllVariantsMustHaveTheSameAssociatedTypes<T> for T {}
+            fn assert_same<A, B>()
+            where
+                A: ?::core::marker::Sized + AllVariantsMustHaveTheSameAssociatedTypes<B>,
+                B: ?::core::marker::Sized,
+            {
+            }
+            #(#checks)*
+        };
+    }
+}
+
+/// Generates the where predicates for the bounds of the given parameters and
+/// the predicates of their where clause.
+fn gen_generics_predicates(generics: &Generics) -> TokenStream2 {
+    let mut out = TokenStream2::new();
+    for param in &generics.params {
+        match param {
+            GenericParam::Type(ty) if !ty.bounds.is_empty() => {
+                let (ident, bounds) = (&ty.ident, &ty.bounds);
+                out.extend(quote! { #ident: #bounds, });
+            }
+            GenericParam::Lifetime(lt) if !lt.bounds.is_empty() => {
+                let (lifetime, bounds) = (&lt.lifetime, &lt.bounds);
+                out.extend(quote! { #lifetime: #bounds, });
+            }
+            _ => {}
+        }
+    }
+    for predicate in generics.where_clause.iter().flat_map(|c| &c.predicates) {
+        out.extend(quote! { #predicate, });
+    }
+
+    out
+}
+
 /// Generates the Fn-trait type (e.g. `FnMut(u32) -> String`) for the given
 /// trait and proxy type (the latter has to be `Fn`, `FnMut` or `FnOnce`!)
 ///
@@ -417,6 +1152,13 @@ fn gen_fn_type_for_trait(
         // The method needs to have a receiver
         (SelfType::None, _) => Some(("Fn-traits", "no", "")),
 
+        // Closures can't be called through a pinned reference
+        (SelfType::PinRef, _) => Some(("Fn-traits", "a `self: Pin<&Self>`", "")),
+        (SelfType::PinMut, _) => Some(("Fn-traits", "a `self: Pin<&mut Self>`", "")),
+        (SelfType::Box, _) => Some(("Fn-traits", "a `self: Box<Self>`", "")),
+        (SelfType::Rc, _) => Some(("Fn-traits", "a `self: Rc<Self>`", "")),
+        (SelfType::Arc, _) => Some(("Fn-traits", "a `self: Arc<Self>`", "")),
+
         // We can't impl methods with `&mut self` or `&self` receiver for
         // `FnOnce`
         (SelfType::Mut, ProxyType::FnOnce) => {
@@ -461,6 +1203,16 @@ fn gen_fn_type_for_trait(
 
     // The return type
     let ret = &sig.output;
+    if SelfReturn::from_sig(sig) != SelfReturn::None {
+        return Err(Error::new(
+            ret.span(),
+            format_args!(
+                "the trait '{}' cannot be implemented for Fn-traits: return types mentioning \
+                `Self` are not allowed",
+                trait_def.ident,
+            ),
+        ));
+    }
 
     // Now it gets a bit complicated. The types of the function signature
     // could contain "local" lifetimes, meaning that they are not declared in
@@ -487,6 +1239,19 @@ fn gen_fn_type_for_trait(
         match arg {
             FnArg::Typed(pat) => {
                 let ty = &pat.ty;
+
+                // A closure's argument types can't refer to `Self`.
+                if SelfArg::from_type(ty) != SelfArg::None {
+                    return Err(Error::new(
+                        ty.span(),
+                        format_args!(
+                            "the trait '{}' cannot be implemented for Fn-traits: argument \
+                            types mentioning `Self` are not allowed",
+                            trait_def.ident,
+                        ),
+                    ));
+                }
+
                 arg_types.append_all(quote! { #ty , });
             }
 
@@ -507,51 +1272,156 @@ fn gen_fn_type_for_trait(
 
 /// Generates the implementation of all items of the given trait. These
 /// implementations together are the body of the `impl` block.
+///
+/// The items forward to `proxy_ty_param`, or to `target` if it's given (a
+/// qualified path like `<Type as Trait>`, used for impl blocks).
 fn gen_items(
     proxy_type: &ProxyType,
     trait_def: &ItemTrait,
     proxy_ty_param: &Ident,
+    target: Option<&TokenStream2>,
 ) -> syn::Result<Vec<TokenStream2>> {
-    trait_def
-        .items
-        .iter()
-        .map(|item| {
-            match item {
-                TraitItem::Const(c) => gen_const_item(proxy_type, c, trait_def, proxy_ty_param),
-                TraitItem::Fn(method) => {
-                    gen_method_item(proxy_type, method, trait_def, proxy_ty_param)
-                }
-                TraitItem::Type(ty) => gen_type_item(proxy_type, ty, trait_def, proxy_ty_param),
-                TraitItem::Macro(mac) => {
-                    // We cannot resolve the macro invocation and thus cannot know
-                    // if it adds additional items to the trait. Thus, we have to
-                    // give up.
-                    Err(Error::new(
-                        mac.span(),
-                        "traits with macro invocations in their bodies are not \
-                        supported by auto_impl",
-                    ))
-                }
-                TraitItem::Verbatim(v) => {
-                    // I don't quite know when this happens, but it's better to
-                    // notify the user with a nice error instead of panicking.
-                    Err(Error::new(
-                        v.span(),
-                        "unexpected 'verbatim'-item (auto-impl doesn't know how to handle it)",
-                    ))
+    // The elements of a tuple can be different types with different
+    // associated items, so only methods with receiver can be forwarded.
+    let is_tuple = matches!(proxy_type, ProxyType::Tuple(_));
+    let is_enum = matches!(proxy_type, ProxyType::Enum(_));
+
+    // Delegating structs forward associated items to the type of their
+    // field, which therefore has to be given.
+    let delegate = match proxy_type {
+        ProxyType::Delegate(d) => Some(d),
+        _ => None,
+    };
+    let delegate_target = |span: Span2, what: String| {
+        let d = delegate.unwrap();
+        match d.field_ty {
+            Some(_) => Ok(gen_delegate_target(d, trait_def)),
+            None => Err(Error::new(
+                span,
+                format_args!(
+                    "the trait `{}` cannot be auto-implemented for `{}` without knowing the type \
+                    of the field, because it has {} (add the type like \
+                    `delegate({} => self.{}: <type>)`)",
+                    trait_def.ident,
+                    d.ty.to_token_stream(),
+                    what,
+                    d.ty.to_token_stream(),
+                    d.field.to_token_stream(),
+                ),
+            )),
+        }
+    };
+    let tuple_error = |span: Span2, reason: &str| {
+        Err(Error::new(
+            span,
+            format_args!(
+                "the trait `{}` cannot be auto-implemented for tuples, because {} (the elements \
+                of a tuple can have different types, so it's unclear which one to use)",
+                trait_def.ident, reason,
+            ),
+        ))
+    };
+
+    let results = trait_def.items.iter().map(|item| {
+        match item {
+            TraitItem::Const(c) if target.is_some() => {
+                gen_const_item(proxy_type, c, trait_def, target.unwrap())
+            }
+            TraitItem::Type(ty) if target.is_some() => {
+                gen_type_item(proxy_type, ty, trait_def, target.unwrap())
+            }
+            TraitItem::Const(c) if is_tuple => tuple_error(
+                c.span(),
+                &format!("it has the associated const `{}`", c.ident),
+            ),
+            TraitItem::Type(ty) if is_tuple => tuple_error(
+                ty.span(),
+                &format!("it has the associated type `{}`", ty.ident),
+            ),
+            TraitItem::Fn(method)
+                if is_tuple
+                    && SelfType::from_sig(&method.sig) == SelfType::None
+                    && !should_keep_default_for(method, proxy_type).unwrap_or(false) =>
+            {
+                tuple_error(method.sig.span(), "this method has no receiver")
+            }
+            TraitItem::Const(c) if is_enum => Err(Error::new(
+                c.span(),
+                format_args!(
+                    "the trait `{}` cannot be auto-implemented for enums, because it has the \
+                    associated const `{}` (the variants could have different values)",
+                    trait_def.ident, c.ident,
+                ),
+            )),
+            TraitItem::Const(c) if delegate.is_some() => {
+                delegate_target(c.span(), format!("the associated const `{}`", c.ident))
+                    .and_then(|target| gen_const_item(proxy_type, c, trait_def, &target))
+            }
+            TraitItem::Const(c) => gen_const_item(proxy_type, c, trait_def, proxy_ty_param),
+            TraitItem::Fn(method) => {
+                gen_method_item(proxy_type, method, trait_def, proxy_ty_param, target)
+            }
+            // Enums use the associated types of their first variant, the
+            // others have to match (see `gen_enum_predicates`).
+            TraitItem::Type(ty) => match proxy_type {
+                ProxyType::Enum(e) => {
+                    let first = &e.variants[0].1;
+                    let (_, trait_generics, _) = trait_def.generics.split_for_impl();
+                    let trait_ident = &trait_def.ident;
+                    let proxied = quote! { <#first as #trait_ident #trait_generics> };
+                    gen_type_item(proxy_type, ty, trait_def, &proxied)
                 }
-                _ => {
-                    // `syn` enums are `non_exhaustive` to be future-proof. If a
-                    // trait contains a kind of item we don't even know about, we
-                    // emit an error.
-                    Err(Error::new(
-                        item.span(),
-                        "unknown trait item (auto-impl doesn't know how to handle it)",
-                    ))
+                ProxyType::Delegate(_) => {
+                    delegate_target(ty.span(), format!("the associated type `{}`", ty.ident))
+                        .and_then(|target| gen_type_item(proxy_type, ty, trait_def, &target))
                 }
+                _ => gen_type_item(proxy_type, ty, trait_def, proxy_ty_param),
+            },
+            TraitItem::Macro(mac) => {
+                // We cannot resolve the macro invocation and thus cannot know
+                // if it adds additional items to the trait. Thus, we have to
+                // give up.
+                Err(Error::new(
+                    mac.span(),
+                    "traits with macro invocations in their bodies are not \
+                        supported by auto_impl",
+                ))
             }
-        })
-        .collect()
+            TraitItem::Verbatim(v) => {
+                // I don't quite know when this happens, but it's better to
+                // notify the user with a nice error instead of panicking.
+                Err(Error::new(
+                    v.span(),
+                    "unexpected 'verbatim'-item (auto-impl doesn't know how to handle it)",
+                ))
+            }
+            _ => {
+                // `syn` enums are `non_exhaustive` to be future-proof. If a
+                // trait contains a kind of item we don't even know about, we
+                // emit an error.
+                Err(Error::new(
+                    item.span(),
+                    "unknown trait item (auto-impl doesn't know how to handle it)",
+                ))
+            }
+        }
+    });
+
+    // Report the problems of all items at once instead of stopping at the
+    // first one.
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(item) => items.push(item),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    match combine_errors(errors) {
+        Some(err) => Err(err),
+        None => Ok(items),
+    }
 }
 
 /// Generates the implementation of an associated const item described by
@@ -563,7 +1433,7 @@ fn gen_const_item(
     proxy_type: &ProxyType,
     item: &TraitItemConst,
     trait_def: &ItemTrait,
-    proxy_ty_param: &Ident,
+    proxy_ty_param: &impl ToTokens,
 ) -> syn::Result<TokenStream2> {
     // A trait with associated consts cannot be implemented for Fn* types.
     if proxy_type.is_fn() {
@@ -594,7 +1464,7 @@ fn gen_type_item(
     proxy_type: &ProxyType,
     item: &TraitItemType,
     trait_def: &ItemTrait,
-    proxy_ty_param: &Ident,
+    proxy_ty_param: &impl ToTokens,
 ) -> syn::Result<TokenStream2> {
     // A trait with associated types cannot be implemented for Fn* types.
     if proxy_type.is_fn() {
@@ -616,6 +1486,18 @@ fn gen_type_item(
     })
 }
 
+/// Generates the qualified path `<FieldType as Trait<..>>` the items of a
+/// delegating struct are forwarded to. If the type of the field is not given,
+/// it's inferred from the receiver with `<_ as Trait<..>>`.
+fn gen_delegate_target(d: &DelegateProxy, trait_def: &ItemTrait) -> TokenStream2 {
+    let (_, trait_generics, _) = trait_def.generics.split_for_impl();
+    let trait_ident = &trait_def.ident;
+    match &d.field_ty {
+        Some(field_ty) => quote! { <#field_ty as #trait_ident #trait_generics> },
+        None => quote! { <_ as #trait_ident #trait_generics> },
+    }
+}
+
 /// Generates the implementation of a method item described by `item`. The
 /// implementation is returned as token stream.
 ///
@@ -627,6 +1509,7 @@ fn gen_method_item(
     item: &TraitItemFn,
     trait_def: &ItemTrait,
     proxy_ty_param: &Ident,
+    target: Option<&TokenStream2>,
 ) -> syn::Result<TokenStream2> {
     // If this method has a `#[auto_impl(keep_default_for(...))]` attribute for
     // the given proxy type, we don't generate anything for this impl block.
@@ -647,19 +1530,62 @@ fn gen_method_item(
 
     // Determine the kind of the method, determined by the self type.
     let sig = &item.sig;
+
+    // The generated body is spanned to the method signature in the trait, so
+    // that errors in it point to the method they originate from.
+    let span = sig.span();
     let self_arg = SelfType::from_sig(sig);
     let attrs = filter_attrs(&item.attrs);
 
     // Check self type and proxy type combination
-    check_receiver_compatible(proxy_type, self_arg, &trait_def.ident, sig.span())?;
+    let receiver_check =
+        check_receiver_compatible(proxy_type, self_arg, &trait_def.ident, sig.span());
+
+    // Check if we can convert a returned `Self` of the proxied type
+    let self_return = SelfReturn::from_sig(sig);
+    let wrap_return =
+        gen_self_return_wrapper(proxy_type, self_return, self_arg, &trait_def.ident, sig);
 
     // Generate the list of argument used to call the method.
-    let (inputs, args) = get_arg_list(sig.inputs.iter())?;
+    let arg_list = get_arg_list(proxy_type, &trait_def.ident, sig.inputs.iter());
 
-    // Construct a signature we'll use to generate the proxy method impl
-    // This is _almost_ the same as the original, except we use the inputs constructed
-    // alongside the args. These may be slightly different than the original trait.
-    let sig = Signature {
+    // For `Option`, determine what to return if there is no value to forward
+    // to.
+    let none_value = if *proxy_type == ProxyType::Option && self_arg != SelfType::None {
+        gen_none_value(item, self_return, &trait_def.ident).map(Some)
+    } else {
+        Ok(None)
+    };
+
+    // For collections, determine how the results of the calls are combined.
+    let fan_out = if proxy_type.is_fan_out() && self_arg != SelfType::None {
+        check_fan_out(item, self_return, proxy_type, &trait_def.ident).map(Some)
+    } else {
+        Ok(None)
+    };
+
+    // Report all of the above problems together.
+    let (wrap_return, (inputs, args), none_value, fan_out) =
+        match (receiver_check, wrap_return, arg_list, none_value, fan_out) {
+            (Ok(()), Ok(wrap_return), Ok(arg_list), Ok(none_value), Ok(fan_out)) => {
+                (wrap_return, arg_list, none_value, fan_out)
+            }
+            (receiver_check, wrap_return, arg_list, none_value, fan_out) => {
+                let errors = receiver_check
+                    .err()
+                    .into_iter()
+                    .chain(wrap_return.err())
+                    .chain(arg_list.err())
+                    .chain(none_value.err())
+                    .chain(fan_out.err());
+                return Err(combine_errors(errors).unwrap());
+            }
+        };
+
+    // Construct a signature we'll use to generate the proxy method impl
+    // This is _almost_ the same as the original, except we use the inputs constructed
+    // alongside the args. These may be slightly different than the original trait.
+    let sig = Signature {
         constness: item.sig.constness,
         asyncness: item.sig.asyncness,
         unsafety: item.sig.unsafety,
@@ -701,91 +1627,504 @@ fn gen_method_item(
         .filter_map(|param| match param {
             GenericParam::Type(param) => {
                 let name = &param.ident;
-                Some(quote! { #name , })
+                Some(quote_spanned! {span=> #name , })
             }
             GenericParam::Const(param) => {
                 let name = &param.ident;
-                Some(quote! { #name , })
+                Some(quote_spanned! {span=> #name , })
             }
             GenericParam::Lifetime(_) => None,
         })
         .collect::<TokenStream2>();
 
-    let generic_types = if generic_types.is_empty() {
-        generic_types
+    // Before Rust 1.63, explicit generic arguments were not allowed for
+    // methods with `impl Trait` arguments. So in that case, we omit the
+    // turbofish if all parameters can be inferred from the arguments. If they
+    // can't, we still have to specify them, which requires a newer compiler.
+    let omit_turbofish = has_impl_trait_arg(&sig) && generic_params_inferable(&sig);
+    let generic_types = if generic_types.is_empty() || omit_turbofish {
+        TokenStream2::new()
     } else {
-        quote! { ::<#generic_types> }
+        quote_spanned! {span=> ::<#generic_types> }
     };
 
     // Generate the body of the function. This mainly depends on the self type,
     // but also on the proxy type.
     let fn_name = &sig.ident;
-    let await_token = sig.asyncness.map(|_| quote! { .await });
+    let await_token = sig.asyncness.map(|_| quote_spanned! {span=> .await });
+    let inner_binding = Ident::new("inner", Span2::mixed_site());
+
+    // Convert the returned `Self` of the proxied type into our proxy type. A
+    // returned `&Self` or `&mut Self` refers to the proxied type, so we
+    // discard it and return our `self` instead (which is what builder
+    // methods return anyway).
+    let wrap_self_return = |body: TokenStream2| match (&wrap_return, self_return) {
+        (Some(new), SelfReturn::Option) => {
+            quote_spanned! {span=> ::core::option::Option::map(#body, #new) }
+        }
+        (Some(new), SelfReturn::Result) => {
+            quote_spanned! {span=> ::core::result::Result::map(#body, #new) }
+        }
+        (Some(new), _) => quote_spanned! {span=> #new(#body) },
+        (None, SelfReturn::Ref) | (None, SelfReturn::Mut)
+            if !proxy_type.is_fn() && !proxy_type.is_fan_out() =>
+        {
+            quote_spanned! {span=>
+                let _ = #body;
+                self
+            }
+        }
+        (None, _) => body,
+    };
+    let enum_proxy = match proxy_type {
+        ProxyType::Enum(e) => Some(e),
+        _ => None,
+    };
+
+    // The type whose method is called: our type parameter, the field of a
+    // delegating struct or the type of an impl block.
+    let callee = match (target, proxy_type) {
+        (Some(target), _) => target.clone(),
+        (None, ProxyType::Delegate(d)) => gen_delegate_target(d, trait_def),
+        (None, _) => proxy_ty_param.to_token_stream(),
+    };
 
     let body = match self_arg {
         // Fn proxy types get a special treatment
         _ if proxy_type.is_fn() => {
-            quote! { ({self})(#args) #await_token }
+            quote_spanned! {span=> ({self})(#args) #await_token }
         }
 
         // No receiver
         SelfType::None => {
             // The proxy type is a reference, smart pointer or Box.
-            quote! { #proxy_ty_param::#fn_name #generic_types(#args) #await_token }
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#args) #await_token }
         }
 
-        // Receiver `self` (by value)
-        SelfType::Value => {
-            // The proxy type is a Box.
-            quote! { #proxy_ty_param::#fn_name #generic_types(*self, #args) #await_token }
+        // Enums dispatch to the value of the current variant.
+        _ if enum_proxy.is_some() => {
+            let e = enum_proxy.unwrap();
+            let (_, trait_generics, _) = trait_def.generics.split_for_impl();
+            let trait_ident = &trait_def.ident;
+            let (value, inner) = match self_arg {
+                SelfType::PinRef => (
+                    quote_spanned! {span=> ::core::pin::Pin::get_ref(self) },
+                    quote_spanned! {span=> ::core::pin::Pin::new(#inner_binding) },
+                ),
+                SelfType::PinMut => (
+                    quote_spanned! {span=> ::core::pin::Pin::get_mut(self) },
+                    quote_spanned! {span=> ::core::pin::Pin::new(#inner_binding) },
+                ),
+                _ => (
+                    quote_spanned! {span=> self },
+                    quote_spanned! {span=> #inner_binding },
+                ),
+            };
+
+            let arms = e.variants.iter().map(|(variant, ty)| {
+                let call = wrap_self_return(quote_spanned! {span=>
+                    <#ty as #trait_ident #trait_generics>::#fn_name #generic_types(#inner, #args)
+                        #await_token
+                });
+                quote_spanned! {span=> Self::#variant(#inner_binding) => { #call } }
+            });
+            quote_spanned! {span=>
+                match #value {
+                    #(#arms)*
+                }
+            }
+        }
+
+        // Collections and tuples forward to each element, the loop or the
+        // calls for each element are added below.
+        _ if proxy_type.is_fan_out() => {
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#inner_binding, #args) #await_token }
+        }
+
+        // `Option` forwards to the value inside of `Some`, the `match` is
+        // added below.
+        _ if *proxy_type == ProxyType::Option => {
+            let inner = if let SelfType::PinRef | SelfType::PinMut = self_arg {
+                quote_spanned! {span=> ::core::pin::Pin::new(#inner_binding) }
+            } else {
+                quote_spanned! {span=> #inner_binding }
+            };
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#inner, #args) #await_token }
+        }
+
+        // Receiver `self` (by value) or `self: Box<Self>`
+        SelfType::Value | SelfType::Box => {
+            // The proxy type owns the proxied value and was checked by
+            // `check_receiver_compatible`.
+            let inner = gen_proxied_access(
+                proxy_type,
+                quote_spanned! {span=> self },
+                Access::Value,
+                span,
+            )
+            .unwrap();
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#inner, #args) #await_token }
         }
 
+        // Receiver `self: Rc<Self>` or `self: Arc<Self>`
+        SelfType::Rc | SelfType::Arc => {
+            // The proxy type is the same pointer. We move the inner pointer
+            // out if we own the only reference and clone it otherwise.
+            let ptr = if self_arg == SelfType::Rc {
+                quote_spanned! {span=> alloc::rc::Rc }
+            } else {
+                quote_spanned! {span=> alloc::sync::Arc }
+            };
+            let inner = quote_spanned! {span=>
+                #ptr::try_unwrap(self).unwrap_or_else(|outer| #ptr::clone(&*outer))
+            };
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#inner, #args) #await_token }
+        }
+
+        // Rejected by `check_receiver_compatible`
+        SelfType::Unknown => unreachable!(),
+
         // `&self` or `&mut self` receiver
         SelfType::Ref | SelfType::Mut => {
             // The proxy type could be anything in the `Ref` case, and `&mut`
-            // or Box in the `Mut` case.
-            quote! { #proxy_ty_param::#fn_name #generic_types(self, #args) #await_token }
+            // or Box in the `Mut` case. Most of them deref to the proxied
+            // type, the others (and delegating structs) give access to it
+            // through a field.
+            let inner = match proxy_type {
+                ProxyType::Wrapping | ProxyType::Reverse | ProxyType::Delegate(_) => {
+                    let access = if self_arg == SelfType::Ref {
+                        Access::Ref
+                    } else {
+                        Access::Mut
+                    };
+                    gen_proxied_access(proxy_type, quote_spanned! {span=> self }, access, span)
+                        .unwrap()
+                }
+                _ => quote_spanned! {span=> self },
+            };
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#inner, #args) #await_token }
+        }
+
+        // `self: Pin<&Self>` or `self: Pin<&mut Self>` receiver
+        SelfType::PinRef | SelfType::PinMut => {
+            // For `Pin<P>`, we can project the pin through the pointer. For
+            // all other proxy types, the pointee is `Unpin` and can be pinned
+            // again. If the type of a delegating struct's field is inferred,
+            // there is no `Unpin` bound for it, so the pinning is spanned to
+            // the field to make errors point there.
+            let span = match proxy_type {
+                ProxyType::Delegate(d) if d.field_ty.is_none() => d.field.as_ref().unwrap().span(),
+                _ => span,
+            };
+            let pinned = match (proxy_type, self_arg) {
+                (ProxyType::Pin, SelfType::PinRef) => quote_spanned! {span=>
+                    ::core::pin::Pin::as_ref(::core::pin::Pin::get_ref(self))
+                },
+                (ProxyType::Pin, _) => quote_spanned! {span=>
+                    ::core::pin::Pin::as_mut(::core::pin::Pin::get_mut(self))
+                },
+                (_, SelfType::PinRef) => {
+                    let proxy = quote_spanned! {span=> ::core::pin::Pin::get_ref(self) };
+                    let inner = gen_proxied_access(proxy_type, proxy, Access::Ref, span).unwrap();
+                    quote_spanned! {span=> ::core::pin::Pin::new(#inner) }
+                }
+                _ => {
+                    let proxy = quote_spanned! {span=> ::core::pin::Pin::get_mut(self) };
+                    let inner = gen_proxied_access(proxy_type, proxy, Access::Mut, span).unwrap();
+                    quote_spanned! {span=> ::core::pin::Pin::new(#inner) }
+                }
+            };
+
+            quote_spanned! {span=> #callee::#fn_name #generic_types(#pinned, #args) #await_token }
         }
     };
 
+    // Enums wrap the returned values for each variant (see above).
+    let body = if enum_proxy.is_some() {
+        body
+    } else {
+        wrap_self_return(body)
+    };
+
+    // Collections and tuples call the method for each element and combine
+    // the results.
+    let body = match fan_out {
+        Some(fan_out) => {
+            let value = Ident::new("value", Span2::mixed_site());
+            let copy_checks = gen_copy_checks(&sig.inputs);
+
+            // What to do with the result of each call.
+            let step = |call: &TokenStream2| match fan_out {
+                None if self_return == SelfReturn::None => quote_spanned! {span=> #call; },
+                None => quote_spanned! {span=> let _ = #call; },
+                Some(FanOut::FirstSome) => quote_spanned! {span=>
+                    let #value = #call;
+                    if ::core::option::Option::is_some(&#value) {
+                        return #value;
+                    }
+                },
+                Some(FanOut::All) => quote_spanned! {span=>
+                    if !#call {
+                        return false;
+                    }
+                },
+                Some(FanOut::Any) => quote_spanned! {span=>
+                    if #call {
+                        return true;
+                    }
+                },
+                Some(FanOut::Collect) => quote_spanned! {span=> #call, },
+            };
+
+            // What to return after all elements are done.
+            let tail = match fan_out {
+                None if self_return == SelfReturn::None => quote_spanned! {span=>},
+                None => quote_spanned! {span=> self },
+                Some(FanOut::FirstSome) => quote_spanned! {span=> ::core::option::Option::None },
+                Some(FanOut::All) => quote_spanned! {span=> true },
+                Some(FanOut::Any) => quote_spanned! {span=> false },
+                Some(FanOut::Collect) => quote_spanned! {span=>},
+            };
+
+            match proxy_type {
+                // The elements of tuples have different types, so we call the
+                // method for each of them explicitly.
+                ProxyType::Tuple(arity) => {
+                    let ty_params = find_tuple_param_names(trait_def, proxy_ty_param, *arity);
+                    let calls = ty_params.iter().enumerate().map(|(i, ty_param)| {
+                        let index = Index {
+                            index: i as u32,
+                            span,
+                        };
+                        let receiver = match self_arg {
+                            SelfType::Ref => quote_spanned! {span=> &self.#index },
+                            SelfType::Mut => quote_spanned! {span=> &mut self.#index },
+                            _ => quote_spanned! {span=> self.#index },
+                        };
+                        quote_spanned! {span=>
+                            #ty_param::#fn_name #generic_types(#receiver, #args) #await_token
+                        }
+                    });
+                    let steps = calls.map(|call| step(&call));
+
+                    if fan_out == Some(FanOut::Collect) {
+                        quote_spanned! {span=>
+                            #copy_checks
+                            ::core::iter::Iterator::collect(::core::iter::Iterator::flatten(
+                                ::core::iter::IntoIterator::into_iter([#(#steps)*])
+                            ))
+                        }
+                    } else {
+                        quote_spanned! {span=> #copy_checks #(#steps)* #tail }
+                    }
+                }
+
+                _ => {
+                    let elems = match self_arg {
+                        SelfType::Ref => quote_spanned! {span=> <[#proxy_ty_param]>::iter(self) },
+                        SelfType::Mut => {
+                            quote_spanned! {span=> <[#proxy_ty_param]>::iter_mut(self) }
+                        }
+                        _ => quote_spanned! {span=> ::core::iter::IntoIterator::into_iter(self) },
+                    };
+
+                    if fan_out == Some(FanOut::Collect) {
+                        quote_spanned! {span=>
+                            #copy_checks
+                            ::core::iter::Iterator::collect(
+                                ::core::iter::Iterator::flat_map(#elems, |#inner_binding| #body)
+                            )
+                        }
+                    } else {
+                        let step = step(&body);
+                        quote_spanned! {span=>
+                            #copy_checks
+                            for #inner_binding in #elems {
+                                #step
+                            }
+                            #tail
+                        }
+                    }
+                }
+            }
+        }
+        None => body,
+    };
+
+    // The `Option` proxy only calls the method if there is a value.
+    let body = match none_value {
+        Some(none_value) => {
+            let option = match self_arg {
+                SelfType::PinRef => quote_spanned! {span=> ::core::pin::Pin::get_ref(self) },
+                SelfType::PinMut => quote_spanned! {span=> ::core::pin::Pin::get_mut(self) },
+                _ => quote_spanned! {span=> self },
+            };
+            quote_spanned! {span=>
+                match #option {
+                    ::core::option::Option::Some(#inner_binding) => { #body }
+                    ::core::option::Option::None => { #none_value }
+                }
+            }
+        }
+        None => body,
+    };
+
     // Combine body with signature
     Ok(quote! { #(#attrs)* #sig { #body }})
 }
 
+/// Generates statements that check that all arguments of the method are
+/// `Copy`, as fan-out proxies pass them to the method of each element. The
+/// checks are spanned to the arguments, so that an error points to the
+/// argument that isn't `Copy` instead of into the generated code.
+fn gen_copy_checks(inputs: &Punctuated<FnArg, Token![,]>) -> TokenStream2 {
+    let assert_copy = Ident::new("arguments_must_be_copy", Span2::mixed_site());
+    let checks = inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(PatType { pat, .. }) => match &**pat {
+                Pat::Ident(p) => Some((arg, &p.ident)),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .map(|(arg, ident)| quote_spanned! {arg.span()=> #assert_copy(&#ident); })
+        .collect::<Vec<_>>();
+    if checks.is_empty() {
+        return TokenStream2::new();
+    }
+
+    quote! {
+        fn #assert_copy<A: ::core::marker::Copy>(_: &A) {}
+        #(#checks)*
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SelfType {
     None,
     Ref,
     Mut,
     Value,
+    PinRef,
+    PinMut,
+    Box,
+    Rc,
+    Arc,
+    Unknown,
 }
 
 impl SelfType {
     fn from_sig(sig: &Signature) -> Self {
         match sig.inputs.iter().next() {
             Some(FnArg::Receiver(r)) => {
-                if r.reference.is_none() {
+                if r.reference.is_some() {
+                    if r.mutability.is_none() {
+                        SelfType::Ref
+                    } else {
+                        SelfType::Mut
+                    }
+                } else if r.colon_token.is_some() {
+                    Self::from_receiver_type(&r.ty)
+                } else {
                     SelfType::Value
-                } else if r.mutability.is_none() {
+                }
+            }
+            _ => SelfType::None,
+        }
+    }
+
+    /// Determines the kind of an explicitly typed receiver like
+    /// `self: Box<Self>` or `self: Pin<&mut Self>`. We only look at the last
+    /// path segment, so `self: alloc::rc::Rc<Self>` is understood as well.
+    fn from_receiver_type(ty: &Type) -> Self {
+        if is_self_type(ty) {
+            return SelfType::Value;
+        }
+
+        let path = match ty {
+            Type::Reference(r) if is_self_type(&r.elem) => {
+                return if r.mutability.is_none() {
                     SelfType::Ref
                 } else {
                     SelfType::Mut
+                };
+            }
+            Type::Path(p) if p.qself.is_none() => &p.path,
+            _ => return SelfType::Unknown,
+        };
+
+        // All receiver types we know have exactly one type parameter.
+        let seg = path.segments.last().unwrap();
+        let inner = match &seg.arguments {
+            PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                match args.args.first() {
+                    Some(GenericArgument::Type(t)) => t,
+                    _ => return SelfType::Unknown,
                 }
             }
-            _ => SelfType::None,
+            _ => return SelfType::Unknown,
+        };
+
+        match (&*seg.ident.to_string(), inner) {
+            ("Box", t) if is_self_type(t) => SelfType::Box,
+            ("Rc", t) if is_self_type(t) => SelfType::Rc,
+            ("Arc", t) if is_self_type(t) => SelfType::Arc,
+            ("Pin", Type::Reference(r)) if is_self_type(&r.elem) => {
+                if r.mutability.is_none() {
+                    SelfType::PinRef
+                } else {
+                    SelfType::PinMut
+                }
+            }
+            _ => SelfType::Unknown,
         }
     }
 
     fn as_str(&self) -> Option<&'static str> {
         match *self {
-            SelfType::None => None,
+            SelfType::None | SelfType::Unknown => None,
             SelfType::Ref => Some("&self"),
             SelfType::Mut => Some("&mut self"),
             SelfType::Value => Some("self"),
+            SelfType::PinRef => Some("self: Pin<&Self>"),
+            SelfType::PinMut => Some("self: Pin<&mut Self>"),
+            SelfType::Box => Some("self: Box<Self>"),
+            SelfType::Rc => Some("self: Rc<Self>"),
+            SelfType::Arc => Some("self: Arc<Self>"),
         }
     }
 }
 
+/// Checks if `Self` appears anywhere in the given type. Paths like
+/// `Self::Item` or `<Self as Trait>::Item` don't count, as they mean the same
+/// type in the generated impl.
+fn mentions_self(ty: &Type) -> bool {
+    struct SelfFinder(bool);
+    impl<'ast> Visit<'ast> for SelfFinder {
+        fn visit_type_path(&mut self, p: &'ast TypePath) {
+            if p.qself.is_none() && p.path.is_ident("Self") {
+                self.0 = true;
+            } else {
+                // We don't look into `p.qself`, see above.
+                visit_path(self, &p.path);
+            }
+        }
+    }
+
+    let mut finder = SelfFinder(false);
+    finder.visit_type(ty);
+    finder.0
+}
+
+/// Checks if the given type is exactly `Self`.
+fn is_self_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p.qself.is_none() && p.path.is_ident("Self"),
+        _ => false,
+    }
+}
+
 /// Checks if this method can be implemented for the given proxy type. If not,
 /// we will emit an error pointing to the method signature.
 fn check_receiver_compatible(
@@ -795,7 +2134,23 @@ fn check_receiver_compatible(
     sig_span: Span2,
 ) -> syn::Result<()> {
     match (proxy_type, self_arg) {
-        (ProxyType::Ref, SelfType::Mut) | (ProxyType::Ref, SelfType::Value) => {
+        (_, SelfType::Unknown) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented, because this method has \
+                    a receiver type auto_impl doesn't know how to forward (only `self`, `&self`, \
+                    `&mut self`, `self: Box<Self>`, `self: Rc<Self>`, `self: Arc<Self>`, \
+                    `self: Pin<&Self>` and `self: Pin<&mut Self>` are supported)",
+                    trait_name),
+            ))
+        }
+
+        (ProxyType::Ref, SelfType::Mut)
+        | (ProxyType::Ref, SelfType::Value)
+        | (ProxyType::Ref, SelfType::PinMut)
+        | (ProxyType::Ref, SelfType::Box)
+        | (ProxyType::Ref, SelfType::Rc)
+        | (ProxyType::Ref, SelfType::Arc) => {
             Err(Error::new(
                 sig_span,
                 format_args!("the trait `{}` cannot be auto-implemented for immutable references, because \
@@ -805,35 +2160,231 @@ fn check_receiver_compatible(
             ))
         }
 
-        (ProxyType::RefMut, SelfType::Value) => {
+        (ProxyType::RefMut, SelfType::Value)
+        | (ProxyType::RefMut, SelfType::Box)
+        | (ProxyType::RefMut, SelfType::Rc)
+        | (ProxyType::RefMut, SelfType::Arc) => {
             Err(Error::new(
                 sig_span,
                 format_args!("the trait `{}` cannot be auto-implemented for mutable references, because \
-                    this method has a `self` receiver (only `&self`, `&mut self` and no receiver are allowed)",
-                trait_name,)
+                    this method has a `{}` receiver (only `&self`, `&mut self` and no receiver are allowed)",
+                trait_name,
+                self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::Box, SelfType::Rc) | (ProxyType::Box, SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for Box, because \
+                    this method has a `{}` receiver (only `&self`, `&mut self`, `self`, \
+                    `self: Box<Self>` and no receiver are allowed)",
+                    trait_name,
+                    self_arg.as_str().unwrap())
             ))
         }
 
         (ProxyType::Rc, SelfType::Mut)
         | (ProxyType::Rc, SelfType::Value)
+        | (ProxyType::Rc, SelfType::PinMut)
+        | (ProxyType::Rc, SelfType::Box)
+        | (ProxyType::Rc, SelfType::Arc)
         | (ProxyType::Arc, SelfType::Mut)
-        | (ProxyType::Arc, SelfType::Value) => {
-            let ptr_name = if *proxy_type == ProxyType::Rc {
-                "Rc"
-            } else {
-                "Arc"
-            };
-
+        | (ProxyType::Arc, SelfType::Value)
+        | (ProxyType::Arc, SelfType::PinMut)
+        | (ProxyType::Arc, SelfType::Box)
+        | (ProxyType::Arc, SelfType::Rc)
+        | (ProxyType::Cow, SelfType::Mut)
+        | (ProxyType::Cow, SelfType::Value)
+        | (ProxyType::Cow, SelfType::PinMut)
+        | (ProxyType::Cow, SelfType::Box)
+        | (ProxyType::Cow, SelfType::Rc)
+        | (ProxyType::Cow, SelfType::Arc) => {
             Err(Error::new(
                 sig_span,
                 format_args!("the trait `{}` cannot be auto-implemented for {}, because \
                     this method has a `{}` receiver (only `&self` and no receiver are allowed)",
                     trait_name,
-                    ptr_name,
+                    proxy_type.name(),
+                    self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::Pin, SelfType::Mut)
+        | (ProxyType::Pin, SelfType::Value)
+        | (ProxyType::Pin, SelfType::Box)
+        | (ProxyType::Pin, SelfType::Rc)
+        | (ProxyType::Pin, SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for Pin, because \
+                    this method has a `{}` receiver (only `&self`, `self: Pin<&Self>`, \
+                    `self: Pin<&mut Self>` and no receiver are allowed)",
+                    trait_name,
+                    self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::ManuallyDrop, SelfType::Box)
+        | (ProxyType::ManuallyDrop, SelfType::Rc)
+        | (ProxyType::ManuallyDrop, SelfType::Arc)
+        | (ProxyType::AssertUnwindSafe, SelfType::Box)
+        | (ProxyType::AssertUnwindSafe, SelfType::Rc)
+        | (ProxyType::AssertUnwindSafe, SelfType::Arc)
+        | (ProxyType::Wrapping, SelfType::Box)
+        | (ProxyType::Wrapping, SelfType::Rc)
+        | (ProxyType::Wrapping, SelfType::Arc)
+        | (ProxyType::Reverse, SelfType::Box)
+        | (ProxyType::Reverse, SelfType::Rc)
+        | (ProxyType::Reverse, SelfType::Arc)
+        | (ProxyType::Option, SelfType::Box)
+        | (ProxyType::Option, SelfType::Rc)
+        | (ProxyType::Option, SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for {}, because \
+                    this method has a `{}` receiver (only `&self`, `&mut self`, `self`, \
+                    `self: Pin<&Self>`, `self: Pin<&mut Self>` and no receiver are allowed)",
+                    trait_name,
+                    proxy_type.name(),
                     self_arg.as_str().unwrap())
             ))
         }
 
+        (ProxyType::Vec, SelfType::PinRef)
+        | (ProxyType::Vec, SelfType::PinMut)
+        | (ProxyType::Vec, SelfType::Box)
+        | (ProxyType::Vec, SelfType::Rc)
+        | (ProxyType::Vec, SelfType::Arc)
+        | (ProxyType::Array, SelfType::PinRef)
+        | (ProxyType::Array, SelfType::PinMut)
+        | (ProxyType::Array, SelfType::Box)
+        | (ProxyType::Array, SelfType::Rc)
+        | (ProxyType::Array, SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for {}, because \
+                    this method has a `{}` receiver (only `&self`, `&mut self`, `self` and no \
+                    receiver are allowed)",
+                    trait_name,
+                    proxy_type.name(),
+                    self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::Enum(_), SelfType::None)
+        | (ProxyType::Enum(_), SelfType::Box)
+        | (ProxyType::Enum(_), SelfType::Rc)
+        | (ProxyType::Enum(_), SelfType::Arc) => {
+            let receiver = match self_arg.as_str() {
+                Some(receiver) => format!("a `{}` receiver", receiver),
+                None => "no receiver".to_owned(),
+            };
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for `{}`, because \
+                    this method has {} (only `&self`, `&mut self`, `self`, \
+                    `self: Pin<&Self>` and `self: Pin<&mut Self>` are allowed)",
+                    trait_name,
+                    proxy_type.name(),
+                    receiver)
+            ))
+        }
+
+        (ProxyType::Delegate(d), SelfType::None) if d.field_ty.is_none() => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for `{}` without knowing \
+                    the type of the field, because this method has no receiver (add the type \
+                    like `delegate({} => self.{}: <type>)`)",
+                    trait_name,
+                    d.ty.to_token_stream(),
+                    d.ty.to_token_stream(),
+                    d.field.to_token_stream())
+            ))
+        }
+
+        (ProxyType::Delegate(d), SelfType::Box)
+        | (ProxyType::Delegate(d), SelfType::Rc)
+        | (ProxyType::Delegate(d), SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for `{}`, because \
+                    this method has a `{}` receiver (only `&self`, `&mut self`, `self`, \
+                    `self: Pin<&Self>`, `self: Pin<&mut Self>` and no receiver are allowed)",
+                    trait_name,
+                    d.ty.to_token_stream(),
+                    self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::Tuple(_), SelfType::PinRef)
+        | (ProxyType::Tuple(_), SelfType::PinMut)
+        | (ProxyType::Tuple(_), SelfType::Box)
+        | (ProxyType::Tuple(_), SelfType::Rc)
+        | (ProxyType::Tuple(_), SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for tuples, because \
+                    this method has a `{}` receiver (only `&self`, `&mut self` and `self` are \
+                    allowed)",
+                    trait_name,
+                    self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::Slice, SelfType::Value)
+        | (ProxyType::Slice, SelfType::PinRef)
+        | (ProxyType::Slice, SelfType::PinMut)
+        | (ProxyType::Slice, SelfType::Box)
+        | (ProxyType::Slice, SelfType::Rc)
+        | (ProxyType::Slice, SelfType::Arc) => {
+            Err(Error::new(
+                sig_span,
+                format_args!("the trait `{}` cannot be auto-implemented for slices, because \
+                    this method has a `{}` receiver (only `&self`, `&mut self` and no receiver \
+                    are allowed)",
+                    trait_name,
+                    self_arg.as_str().unwrap())
+            ))
+        }
+
+        (ProxyType::Custom(custom), _) => {
+            // Which capability of the custom pointer is needed to forward
+            // this receiver.
+            let capability = match self_arg {
+                SelfType::Ref | SelfType::PinRef if !custom.deref => Some("deref"),
+                SelfType::Mut | SelfType::PinMut if !custom.deref_mut => Some("deref_mut"),
+                SelfType::Value if !custom.into_inner => Some("into_inner"),
+                SelfType::Box | SelfType::Rc | SelfType::Arc => {
+                    return Err(Error::new(
+                        sig_span,
+                        format_args!("the trait `{}` cannot be auto-implemented for `{}`, because \
+                            this method has a `{}` receiver (custom proxy types cannot forward \
+                            `Box`, `Rc` or `Arc` receivers)",
+                            trait_name,
+                            proxy_type.name(),
+                            self_arg.as_str().unwrap())
+                    ));
+                }
+                _ => None,
+            };
+
+            match capability {
+                Some(capability) => Err(Error::new(
+                    sig_span,
+                    format_args!("the trait `{}` cannot be auto-implemented for `{}`, because \
+                        this method has a `{}` receiver (the custom proxy type needs the \
+                        `{}` capability for that)",
+                        trait_name,
+                        proxy_type.name(),
+                        self_arg.as_str().unwrap(),
+                        capability)
+                )),
+                None => Ok(()),
+            }
+        }
+
         (ProxyType::Fn, _) | (ProxyType::FnMut, _) | (ProxyType::FnOnce, _) => {
             // The Fn-trait being compatible with the receiver was already
             // checked before (in `gen_fn_type_for_trait()`).
@@ -844,59 +2395,563 @@ fn check_receiver_compatible(
     }
 }
 
+/// How `Self` is used in the type of a method argument other than the
+/// receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelfArg {
+    None,
+    Value,
+    Ref,
+    Mut,
+    OptionValue,
+    OptionRef,
+    OptionMut,
+    Other,
+}
+
+impl SelfArg {
+    fn from_type(ty: &Type) -> Self {
+        if !mentions_self(ty) {
+            return SelfArg::None;
+        }
+
+        // Strip an optional `Option<...>` around the type.
+        let (ty, in_option) = match ty {
+            Type::Path(p) if p.qself.is_none() => {
+                let seg = p.path.segments.last().unwrap();
+                match &seg.arguments {
+                    PathArguments::AngleBracketed(args)
+                        if seg.ident == "Option" && args.args.len() == 1 =>
+                    {
+                        match args.args.first() {
+                            Some(GenericArgument::Type(t)) => (t, true),
+                            _ => return SelfArg::Other,
+                        }
+                    }
+                    _ => (ty, false),
+                }
+            }
+            _ => (ty, false),
+        };
+
+        match ty {
+            _ if is_self_type(ty) && in_option => SelfArg::OptionValue,
+            _ if is_self_type(ty) => SelfArg::Value,
+            Type::Reference(r) if is_self_type(&r.elem) => match (r.mutability, in_option) {
+                (None, false) => SelfArg::Ref,
+                (None, true) => SelfArg::OptionRef,
+                (Some(_), false) => SelfArg::Mut,
+                (Some(_), true) => SelfArg::OptionMut,
+            },
+            _ => SelfArg::Other,
+        }
+    }
+}
+
+/// How `Self` is used in the return type of a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelfReturn {
+    None,
+    Value,
+    Option,
+    Result,
+    Ref,
+    Mut,
+    Other,
+}
+
+impl SelfReturn {
+    fn from_sig(sig: &Signature) -> Self {
+        let ty = match &sig.output {
+            ReturnType::Type(_, ty) => &**ty,
+            ReturnType::Default => return SelfReturn::None,
+        };
+
+        if !mentions_self(ty) {
+            return SelfReturn::None;
+        }
+        if is_self_type(ty) {
+            return SelfReturn::Value;
+        }
+
+        // Check for `&Self` and `&mut Self` (as returned by builder methods)
+        if let Type::Reference(r) = ty {
+            if is_self_type(&r.elem) {
+                return if r.mutability.is_none() {
+                    SelfReturn::Ref
+                } else {
+                    SelfReturn::Mut
+                };
+            }
+        }
+
+        // Check for `Option<Self>` and `Result<Self, E>`
+        if let Type::Path(p) = ty {
+            let seg = p.path.segments.last().unwrap();
+            if let PathArguments::AngleBracketed(args) = &seg.arguments {
+                let mut types = args.args.iter().filter_map(|arg| match arg {
+                    GenericArgument::Type(t) => Some(t),
+                    _ => None,
+                });
+                let first_is_self = types.next().map_or(false, is_self_type);
+                let others_mention_self = types.any(mentions_self);
+
+                if p.qself.is_none() && first_is_self && !others_mention_self {
+                    match (&*seg.ident.to_string(), args.args.len()) {
+                        ("Option", 1) => return SelfReturn::Option,
+                        ("Result", 2) => return SelfReturn::Result,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        SelfReturn::Other
+    }
+}
+
+/// Determines how the results of calling the given method on each element of
+/// a collection are combined. Methods returning nothing (or `&Self`/`&mut
+/// Self`, which are discarded in favor of `self`) don't need a reduction, all
+/// others need a `#[auto_impl(fan_out = ...)]` attribute.
+fn check_fan_out(
+    item: &TraitItemFn,
+    self_return: SelfReturn,
+    proxy_type: &ProxyType,
+    trait_name: &Ident,
+) -> syn::Result<Option<FanOut>> {
+    let fan_out = fan_out_reduction(item)?;
+    let ty = match &item.sig.output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => return Ok(None),
+    };
+    if let SelfReturn::Ref | SelfReturn::Mut = self_return {
+        return Ok(None);
+    }
+    if let Type::Tuple(tuple) = &**ty {
+        if tuple.elems.is_empty() {
+            return Ok(None);
+        }
+    }
+
+    match fan_out {
+        // The results of async methods cannot be awaited in a closure.
+        Some(FanOut::Collect) if item.sig.asyncness.is_some() => Err(Error::new(
+            item.sig.span(),
+            format_args!(
+                "the trait `{}` cannot be auto-implemented for {}, because this method is \
+                `async` (`fan_out = collect` is not supported for async methods)",
+                trait_name,
+                proxy_type.name(),
+            ),
+        )),
+        Some(fan_out) => Ok(Some(fan_out)),
+        None => Err(Error::new(
+            ty.span(),
+            format_args!(
+                "the trait `{}` cannot be auto-implemented for {}, because this method returns \
+                a value and it's unclear how to combine the values returned for each element \
+                (specify it with `#[auto_impl(fan_out = first_some | all | any | collect)]`)",
+                trait_name,
+                proxy_type.name(),
+            ),
+        )),
+    }
+}
+
+/// Generates the value returned by the method of the `Option` proxy if it is
+/// `None`: the expression given with `#[auto_impl(on_none = ...)]`, nothing
+/// for methods returning `()`, `self` for builder methods, `None` for methods
+/// returning `Self` and `Default::default()` otherwise. Return types that
+/// can't implement `Default` are rejected.
+fn gen_none_value(
+    item: &TraitItemFn,
+    self_return: SelfReturn,
+    trait_name: &Ident,
+) -> syn::Result<TokenStream2> {
+    if let Some(expr) = on_none_expr(item)? {
+        return Ok(expr.into_token_stream());
+    }
+
+    let ty = match &item.sig.output {
+        ReturnType::Default => return Ok(quote! { () }),
+        ReturnType::Type(_, ty) => ty,
+    };
+    match self_return {
+        SelfReturn::Ref | SelfReturn::Mut => return Ok(quote! { self }),
+        SelfReturn::Value => return Ok(quote! { ::core::option::Option::None }),
+        _ => {}
+    }
+
+    let reason = match &**ty {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => return Ok(quote! { () }),
+        Type::Reference(_) => Some("a reference"),
+        Type::Ptr(_) => Some("a raw pointer"),
+        Type::BareFn(_) => Some("a function pointer"),
+        Type::ImplTrait(_) => Some("an `impl Trait` type"),
+        Type::Never(_) => Some("`!`"),
+        Type::Path(p)
+            if p.path
+                .segments
+                .last()
+                .map_or(false, |s| s.ident == "Result") =>
+        {
+            Some("a `Result`")
+        }
+        _ => None,
+    };
+    if let Some(reason) = reason {
+        return Err(Error::new(
+            ty.span(),
+            format_args!(
+                "the trait `{}` cannot be auto-implemented for `Option`, because this method \
+                returns {}, which cannot be created if the `Option` is `None` (specify the \
+                returned value with `#[auto_impl(on_none = ...)]`)",
+                trait_name, reason,
+            ),
+        ));
+    }
+
+    // Spanned to the return type, so that a missing `Default` impl points
+    // to it.
+    Ok(quote_spanned! {ty.span()=> ::core::default::Default::default() })
+}
+
+/// Checks if the `&Self` or `&mut Self` returned by the given method can only
+/// be the receiver: no other argument mentions `Self` and the lifetime of the
+/// returned reference is elided or the one of the receiver.
+fn returns_receiver(sig: &Signature) -> bool {
+    let others_mention_self = sig.inputs.iter().any(|arg| match arg {
+        FnArg::Typed(arg) => mentions_self(&arg.ty),
+        FnArg::Receiver(_) => false,
+    });
+    if others_mention_self {
+        return false;
+    }
+
+    let returned_lifetime = match &sig.output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Reference(r) => &r.lifetime,
+            _ => return false,
+        },
+        ReturnType::Default => return false,
+    };
+    let receiver_lifetime = match sig.receiver().map(|r| &*r.ty) {
+        Some(Type::Reference(r)) => &r.lifetime,
+        _ => return false,
+    };
+    match (returned_lifetime, receiver_lifetime) {
+        (None, _) => true,
+        (Some(returned), Some(receiver)) => returned == receiver,
+        (Some(_), None) => false,
+    }
+}
+
+/// Generates the function that converts a `Self` of the proxied type returned
+/// by the method into our proxy type, e.g. `Box::new`. Returns `None` if the
+/// method doesn't return `Self` by value and an error if we cannot convert it
+/// for this proxy type.
+///
+/// Methods returning `&Self` or `&mut Self` don't need a conversion function
+/// (we return `self` instead), but we check that they have a suitable
+/// receiver.
+fn gen_self_return_wrapper(
+    proxy_type: &ProxyType,
+    self_return: SelfReturn,
+    self_arg: SelfType,
+    trait_name: &Ident,
+    sig: &Signature,
+) -> syn::Result<Option<TokenStream2>> {
+    if self_return == SelfReturn::None || proxy_type.is_fn() {
+        return Ok(None);
+    }
+
+    // We can only return `self` if it has the correct type
+    let receiver_ok = match self_return {
+        SelfReturn::Ref => matches!(self_arg, SelfType::Ref | SelfType::Mut),
+        SelfReturn::Mut => self_arg == SelfType::Mut,
+        _ => true,
+    };
+    if !receiver_ok {
+        let (ret, receivers) = if self_return == SelfReturn::Ref {
+            ("&Self", "`&self` or `&mut self`")
+        } else {
+            ("&mut Self", "`&mut self`")
+        };
+
+        return Err(Error::new(
+            sig.output.span(),
+            format_args!(
+                "the trait `{}` cannot be auto-implemented, because this method returns `{}` \
+                (auto_impl can only forward this return type for methods with a {} receiver, \
+                by returning `self`)",
+                trait_name, ret, receivers,
+            ),
+        ));
+    }
+    if let SelfReturn::Ref | SelfReturn::Mut = self_return {
+        if !returns_receiver(sig) {
+            return Err(Error::new(
+                sig.output.span(),
+                format_args!(
+                    "the trait `{}` cannot be auto-implemented, because this method returns a \
+                    reference to `Self` that might not be the receiver (auto_impl forwards this \
+                    return type by returning `self`, which is only correct if no other argument \
+                    mentions `Self` and the returned reference has the lifetime of the \
+                    receiver)",
+                    trait_name,
+                ),
+            ));
+        }
+        return Ok(None);
+    }
+
+    // Only owning smart pointers and wrappers can be created from the value
+    // returned by the proxied type.
+    let new = match proxy_type {
+        ProxyType::Box if self_return != SelfReturn::Other => {
+            quote! { alloc::boxed::Box::new }
+        }
+        ProxyType::Rc if self_return != SelfReturn::Other => quote! { alloc::rc::Rc::new },
+        ProxyType::Arc if self_return != SelfReturn::Other => quote! { alloc::sync::Arc::new },
+        ProxyType::ManuallyDrop if self_return != SelfReturn::Other => {
+            quote! { ::core::mem::ManuallyDrop::new }
+        }
+        ProxyType::AssertUnwindSafe if self_return != SelfReturn::Other => {
+            quote! { ::core::panic::AssertUnwindSafe }
+        }
+        ProxyType::Wrapping if self_return != SelfReturn::Other => quote! { ::core::num::Wrapping },
+        ProxyType::Reverse if self_return != SelfReturn::Other => quote! { ::core::cmp::Reverse },
+        ProxyType::Option if self_return != SelfReturn::Other => {
+            quote! { ::core::option::Option::Some }
+        }
+        // Each variant has a `From` impl.
+        ProxyType::Enum(_) if self_return != SelfReturn::Other => {
+            quote! { ::core::convert::From::from }
+        }
+        _ => {
+            let reason = if self_return == SelfReturn::Other {
+                "`Self` is used in the return type in a way auto_impl cannot convert (only \
+                `Self`, `Option<Self>`, `Result<Self, E>`, `&Self` and `&mut Self` are \
+                supported)"
+            } else {
+                "the proxy type cannot be created from the value returned by the proxied type \
+                (only `Box`, `Rc`, `Arc`, `ManuallyDrop`, `AssertUnwindSafe`, `Wrapping`, \
+                `Reverse`, `Option` and enums can wrap it)"
+            };
+
+            return Err(Error::new(
+                sig.output.span(),
+                format_args!(
+                    "the trait `{}` cannot be auto-implemented for `{}`, because this method \
+                    returns `Self` and {}",
+                    trait_name,
+                    proxy_type.name(),
+                    reason,
+                ),
+            ));
+        }
+    };
+
+    Ok(Some(new))
+}
+
+/// Generates the expression passing the argument `arg`, in which `Self` is
+/// used as described by `self_arg`, to the method of the proxied type. Returns
+/// `None` if this is not possible for the given proxy type.
+fn gen_self_arg_forward(
+    proxy_type: &ProxyType,
+    self_arg: SelfArg,
+    arg: &Ident,
+) -> Option<TokenStream2> {
+    let span = Span2::call_site();
+    let access = |x: TokenStream2, access| gen_proxied_access(proxy_type, x, access, span);
+
+    // The parameter of the closures uses `mixed_site` hygiene, so it cannot
+    // shadow or capture an argument of the user.
+    let x = Ident::new("__auto_impl_x", Span2::mixed_site());
+    let forward = match self_arg {
+        SelfArg::None => quote! { #arg },
+        SelfArg::Ref => access(quote! { #arg }, Access::Ref)?,
+        SelfArg::Mut => access(quote! { #arg }, Access::Mut)?,
+        SelfArg::Value => access(quote! { #arg }, Access::Value)?,
+        SelfArg::OptionRef => {
+            let inner = access(quote! { #x }, Access::Ref)?;
+            quote! { ::core::option::Option::map(#arg, |#x| #inner) }
+        }
+        SelfArg::OptionMut => {
+            let inner = access(quote! { #x }, Access::Mut)?;
+            quote! { ::core::option::Option::map(#arg, |#x| #inner) }
+        }
+        SelfArg::OptionValue => {
+            let inner = access(quote! { #x }, Access::Value)?;
+            quote! { ::core::option::Option::map(#arg, |#x| #inner) }
+        }
+        _ => return None,
+    };
+
+    Some(forward)
+}
+
+/// How the proxied type is accessed through a value of the proxy type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    /// `&T` from a `&P`.
+    Ref,
+    /// `&mut T` from a `&mut P`.
+    Mut,
+    /// `T` from a `P`.
+    Value,
+}
+
+/// Generates an expression that accesses the proxied type through `proxy`,
+/// which is a `&P`, `&mut P` or `P` (depending on `access`) where `P` is the
+/// proxy type. Returns `None` if the proxy type doesn't support that access.
+fn gen_proxied_access(
+    proxy_type: &ProxyType,
+    proxy: TokenStream2,
+    access: Access,
+    span: Span2,
+) -> Option<TokenStream2> {
+    let expr = match (proxy_type, access) {
+        // `Wrapping` and `Reverse` don't implement `Deref`, but their
+        // content is a public field.
+        (ProxyType::Wrapping, Access::Ref) | (ProxyType::Reverse, Access::Ref) => {
+            quote_spanned! {span=> &#proxy.0 }
+        }
+        (ProxyType::Wrapping, Access::Mut) | (ProxyType::Reverse, Access::Mut) => {
+            quote_spanned! {span=> &mut #proxy.0 }
+        }
+        (ProxyType::Wrapping, Access::Value)
+        | (ProxyType::Reverse, Access::Value)
+        | (ProxyType::AssertUnwindSafe, Access::Value) => quote_spanned! {span=> #proxy.0 },
+
+        (ProxyType::Ref, Access::Ref)
+        | (ProxyType::RefMut, Access::Ref)
+        | (ProxyType::Box, Access::Ref)
+        | (ProxyType::Rc, Access::Ref)
+        | (ProxyType::Arc, Access::Ref)
+        | (ProxyType::Cow, Access::Ref)
+        | (ProxyType::Pin, Access::Ref)
+        | (ProxyType::ManuallyDrop, Access::Ref)
+        | (ProxyType::AssertUnwindSafe, Access::Ref) => quote_spanned! {span=> &**#proxy },
+        (ProxyType::RefMut, Access::Mut)
+        | (ProxyType::Box, Access::Mut)
+        | (ProxyType::ManuallyDrop, Access::Mut)
+        | (ProxyType::AssertUnwindSafe, Access::Mut) => quote_spanned! {span=> &mut **#proxy },
+
+        (ProxyType::Box, Access::Value) => quote_spanned! {span=> *#proxy },
+        (ProxyType::ManuallyDrop, Access::Value) => {
+            quote_spanned! {span=> ::core::mem::ManuallyDrop::into_inner(#proxy) }
+        }
+
+        (ProxyType::Delegate(d), access) => {
+            let field = d.field.as_ref().unwrap();
+            match access {
+                Access::Ref => quote_spanned! {span=> &#proxy.#field },
+                Access::Mut => quote_spanned! {span=> &mut #proxy.#field },
+                Access::Value => quote_spanned! {span=> #proxy.#field },
+            }
+        }
+
+        (ProxyType::Custom(custom), Access::Ref) if custom.deref => {
+            quote_spanned! {span=> &**#proxy }
+        }
+        (ProxyType::Custom(custom), Access::Mut) if custom.deref_mut => {
+            quote_spanned! {span=> &mut **#proxy }
+        }
+        (ProxyType::Custom(custom), Access::Value) if custom.into_inner => {
+            quote_spanned! {span=> Self::into_inner(#proxy) }
+        }
+
+        _ => return None,
+    };
+
+    Some(expr)
+}
+
 /// Generates a list of comma-separated arguments used to call the function.
-/// Currently, only simple names are valid and more complex pattern will lead
-/// to an error being emitted. `self` parameters are ignored.
+/// Arguments with patterns other than simple names (like `_` or `(a, b)`) get
+/// fresh names in the generated method. `self` parameters are ignored.
+/// Arguments whose type mentions `Self` are converted to the proxied type if
+/// possible.
 fn get_arg_list<'a>(
+    proxy_type: &ProxyType,
+    trait_name: &Ident,
     original_inputs: impl Iterator<Item = &'a FnArg>,
 ) -> syn::Result<(Punctuated<FnArg, Token![,]>, TokenStream2)> {
     let mut args = TokenStream2::new();
     let mut inputs = Punctuated::new();
 
     let mut r: Result<(), Error> = Ok(());
-    for arg in original_inputs {
+    for (i, arg) in original_inputs.enumerate() {
         match arg {
             FnArg::Typed(arg) => {
-                // Make sure the argument pattern is a simple name. In
-                // principle, we could probably support patterns, but it's
-                // not that important now.
-                if let Pat::Ident(PatIdent {
-                    by_ref: _by_ref,
-                    mutability: _mutability,
-                    ident,
-                    subpat: None,
-                    attrs,
-                }) = &*arg.pat
-                {
-                    // Add name plus trailing comma to tokens
-                    args.append_all(quote! { #ident , });
-
-                    // Add an input argument that omits the `mut` and `ref` pattern bindings
-                    inputs.push(FnArg::Typed(PatType {
-                        attrs: arg.attrs.clone(),
-                        pat: Box::new(Pat::Ident(PatIdent {
-                            attrs: attrs.clone(),
-                            by_ref: None,
-                            mutability: None,
-                            ident: ident.clone(),
-                            subpat: None,
-                        })),
-                        colon_token: arg.colon_token,
-                        ty: arg.ty.clone(),
-                    }))
+                // If the argument pattern is a simple name, we can use that
+                // name. Otherwise we don't care about the pattern (it's only
+                // relevant for the body of the method) and use a fresh name.
+                // That name uses `mixed_site` hygiene, so it cannot conflict
+                // with any name the user wrote.
+                let (ident, pat_attrs) = match &*arg.pat {
+                    Pat::Ident(PatIdent {
+                        ident,
+                        subpat: None,
+                        attrs,
+                        ..
+                    }) => (ident.clone(), attrs.clone()),
+                    _ => (
+                        Ident::new(&format!("arg{}", i), Span2::mixed_site()),
+                        Vec::new(),
+                    ),
+                };
+
+                // Closures get the arguments unchanged (and can't have
+                // arguments mentioning `Self` anyway).
+                let self_arg = if proxy_type.is_fn() {
+                    SelfArg::None
                 } else {
-                    let err = Error::new(
-                        arg.pat.span(), "argument patterns are not supported by #[auto-impl]; use a simple name like \"foo\" (but not `_`)"
-                    );
+                    SelfArg::from_type(&arg.ty)
+                };
 
-                    if let Err(ref mut current_err) = r {
-                        current_err.combine(err);
-                    } else {
-                        r = Err(err);
+                // Add name (converted if necessary) plus trailing comma to
+                // tokens
+                match gen_self_arg_forward(proxy_type, self_arg, &ident) {
+                    Some(forward) => args.append_all(quote! { #forward , }),
+                    None => {
+                        let err = Error::new(
+                            arg.ty.span(),
+                            format_args!(
+                                "the trait `{}` cannot be auto-implemented for `{}`, because \
+                                the argument `{}` has a type mentioning `Self` that cannot \
+                                be converted to the proxied type",
+                                trait_name,
+                                proxy_type.name(),
+                                arg.pat.to_token_stream(),
+                            ),
+                        );
+
+                        if let Err(ref mut current_err) = r {
+                            current_err.combine(err);
+                        } else {
+                            r = Err(err);
+                        }
                     }
-
-                    continue;
                 }
+
+                // Add an input argument that omits the `mut` and `ref` pattern
+                // bindings and any other patterns
+                inputs.push(FnArg::Typed(PatType {
+                    attrs: arg.attrs.clone(),
+                    pat: Box::new(Pat::Ident(PatIdent {
+                        attrs: pat_attrs,
+                        by_ref: None,
+                        mutability: None,
+                        ident,
+                        subpat: None,
+                    })),
+                    colon_token: arg.colon_token,
+                    ty: arg.ty.clone(),
+                }))
             }
 
             // There is only one such argument. We handle it elsewhere and
@@ -910,34 +2965,130 @@ fn get_arg_list<'a>(
     r.map(|_| (inputs, args))
 }
 
+/// Checks if any argument of the given method has an `impl Trait` type.
+fn has_impl_trait_arg(sig: &Signature) -> bool {
+    struct ImplTraitFinder(bool);
+    impl<'ast> Visit<'ast> for ImplTraitFinder {
+        fn visit_type_impl_trait(&mut self, _: &'ast TypeImplTrait) {
+            self.0 = true;
+        }
+    }
+
+    let mut finder = ImplTraitFinder(false);
+    for arg in &sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            finder.visit_type(&arg.ty);
+        }
+    }
+    finder.0
+}
+
+/// Checks if all type and const parameters of the given method are used in
+/// the types of its arguments such that they can be inferred when calling the
+/// method. Parameters only used in projections (like `V::Item`) or in
+/// `impl Trait` types cannot be inferred.
+fn generic_params_inferable(sig: &Signature) -> bool {
+    struct ParamCollector<'ast>(HashSet<&'ast Ident>);
+    impl<'ast> Visit<'ast> for ParamCollector<'ast> {
+        fn visit_type_path(&mut self, p: &'ast TypePath) {
+            if p.qself.is_none() {
+                if let Some(ident) = p.path.get_ident() {
+                    self.0.insert(ident);
+                }
+            }
+
+            // We only look at the generic arguments of the path, as
+            // parameters in `qself` or as path prefix are not inferable.
+            for seg in &p.path.segments {
+                visit_path_arguments(self, &seg.arguments);
+            }
+        }
+
+        fn visit_expr_path(&mut self, e: &'ast ExprPath) {
+            // Const parameters, e.g. in `[u8; N]`
+            if let Some(ident) = e.path.get_ident() {
+                self.0.insert(ident);
+            }
+        }
+
+        fn visit_type_impl_trait(&mut self, _: &'ast TypeImplTrait) {}
+    }
+
+    let mut collector = ParamCollector(HashSet::new());
+    for arg in &sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            collector.visit_type(&arg.ty);
+        }
+    }
+
+    sig.generics.params.iter().all(|param| match param {
+        GenericParam::Type(param) => collector.0.contains(&param.ident),
+        GenericParam::Const(param) => collector.0.contains(&param.ident),
+        GenericParam::Lifetime(_) => true,
+    })
+}
+
 /// Checks if the given method has the attribute `#[auto_impl(keep_default_for(...))]`
 /// and if it contains the given proxy type.
 fn should_keep_default_for(m: &TraitItemFn, proxy_type: &ProxyType) -> syn::Result<bool> {
-    // Get an iterator of just the attribute we are interested in.
-    let mut it = m
-        .attrs
-        .iter()
-        .filter(|attr| is_our_attr(attr))
-        .map(parse_our_attr);
+    Ok(keep_default_for_types(m)?.contains(proxy_type))
+}
 
-    // Check the first (and hopefully only) `keep_default_for` attribute.
-    let out = match it.next() {
-        Some(attr) => {
-            // Check if the attribute lists the given proxy type.
-            let OurAttr::KeepDefaultFor(proxy_types) = attr?;
-            proxy_types.contains(proxy_type)
+/// Returns the proxy types listed in the `#[auto_impl(keep_default_for(...))]`
+/// attribute of the given method or an empty list if there is no such
+/// attribute.
+fn keep_default_for_types(m: &TraitItemFn) -> syn::Result<Vec<ProxyType>> {
+    let mut out = None;
+    for attr in m.attrs.iter().filter(|attr| is_our_attr(attr)) {
+        if let OurAttr::KeepDefaultFor(proxy_types) = parse_our_attr(attr)? {
+            // We disallow more than one such attribute
+            if out.is_some() {
+                return Err(Error::new(
+                    m.sig.span(),
+                    "found two `keep_default_for` attributes on one method",
+                ));
+            }
+            out = Some(proxy_types);
         }
+    }
 
-        // If there is no such attribute, we return `false`
-        None => false,
-    };
+    // If there is no such attribute, no proxy type is listed
+    Ok(out.unwrap_or_default())
+}
 
-    // Check if there is another such attribute (which we disallow)
-    if it.next().is_some() {
-        return Err(Error::new(
-            m.sig.span(),
-            "found two `keep_default_for` attributes on one method",
-        ));
+/// Returns the expression given in the `#[auto_impl(on_none = ...)]`
+/// attribute of the given method, if there is one.
+fn on_none_expr(m: &TraitItemFn) -> syn::Result<Option<Expr>> {
+    let mut out = None;
+    for attr in m.attrs.iter().filter(|attr| is_our_attr(attr)) {
+        if let OurAttr::OnNone(expr) = parse_our_attr(attr)? {
+            if out.is_some() {
+                return Err(Error::new(
+                    m.sig.span(),
+                    "found two `on_none` attributes on one method",
+                ));
+            }
+            out = Some(expr);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Returns the reduction given in the `#[auto_impl(fan_out = ...)]`
+/// attribute of the given method, if there is one.
+fn fan_out_reduction(m: &TraitItemFn) -> syn::Result<Option<FanOut>> {
+    let mut out = None;
+    for attr in m.attrs.iter().filter(|attr| is_our_attr(attr)) {
+        if let OurAttr::FanOut(fan_out) = parse_our_attr(attr)? {
+            if out.is_some() {
+                return Err(Error::new(
+                    m.sig.span(),
+                    "found two `fan_out` attributes on one method",
+                ));
+            }
+            out = Some(fan_out);
+        }
     }
 
     Ok(out)
@@ -29,8 +29,9 @@
 //! # Basic syntax and supported types
 //!
 //! You can annotate your trait with the `#[auto_impl(...)]` attribute. That
-//! attribute can only be used on traits and not on structs, enums or anything
-//! else.
+//! attribute can only be used on traits, on impl blocks of traits (see
+//! [below](#impl-blocks-of-foreign-traits)) and on inline modules (see
+//! [below](#modules)), not on structs, enums or anything else.
 //!
 //! In the attribute, you have to specify all so called *proxy types* (the
 //! types you want to generate impls for) as a comma separated list. Each proxy
@@ -45,9 +46,34 @@
 //! | `Box`        | `impl<T: Trait> Trait for Box<T>` |
 //! | `Rc`         | `impl<T: Trait> Trait for Rc<T>` |
 //! | `Arc`        | `impl<T: Trait> Trait for Arc<T>` |
+//! | `Cow`        | `impl<'a, B: ?Sized + ToOwned + Trait> Trait for Cow<'a, B>` |
+//! | `Pin`        | `impl<P: Deref<Target = T>, T: Trait> Trait for Pin<P>` |
+//! | `ManuallyDrop` | `impl<T: Trait> Trait for ManuallyDrop<T>` |
+//! | `AssertUnwindSafe` | `impl<T: Trait> Trait for AssertUnwindSafe<T>` |
+//! | `Wrapping`   | `impl<T: Trait> Trait for Wrapping<T>` |
+//! | `Reverse`    | `impl<T: Trait> Trait for Reverse<T>` |
+//! | `Option`     | `impl<T: Trait> Trait for Option<T>` |
+//! | `Vec`        | `impl<T: Trait> Trait for Vec<T>` |
+//! | `slice`      | `impl<T: Trait> Trait for [T]` |
+//! | `array`      | `impl<T: Trait, const N: usize> Trait for [T; N]` |
+//! | `tuples(1..=3)` | `impl<T0: Trait> Trait for (T0,)`, ..., `impl<T0: Trait, T1: Trait, T2: Trait> Trait for (T0, T1, T2)` |
 //! | `Fn`         | `impl<T: Fn()> Trait for T` |
 //! | `FnMut`      | `impl<T: FnMut()> Trait for T` |
 //! | `FnOnce`     | `impl<T: FnOnce()> Trait for T` |
+//! | `enum E(A, B)` | `enum E { A(A), B(B) }` and `impl Trait for E` |
+//! | `delegate(S => self.inner)` | `impl Trait for S` (forwarding to `self.inner`) |
+//! | `custom(Ptr<T>, deref)` | `impl<T: Trait> Trait for Ptr<T> where Ptr<T>: Deref<Target = T>` |
+//!
+//! The `Cow` proxy forwards to the borrowed type (e.g. `str` for
+//! `Cow<'_, str>`), which is useful for traits implemented on unsized types
+//! like `str`, `[T]` or `Path`.
+//!
+//! `ManuallyDrop`, `AssertUnwindSafe`, `Wrapping` and `Reverse` are
+//! transparent wrappers from `core`. They own their content, so they support
+//! the same receivers as `Box` except `self: Box<Self>`, and methods
+//! returning `Self` wrap the returned value again. `Wrapping` and `Reverse`
+//! don't implement `Deref`; their content is accessed through the public
+//! field instead. All of them except `ManuallyDrop` require `T: Sized`.
 //!
 //!
 //! # More examples
@@ -139,12 +165,57 @@
 //! implemented for a proxy type, you cannot implement the trait for that proxy
 //! type.
 //!
-//! | Trait contains method with... | `&` | `&mut` | `Box` | `Rc` | `Arc` |
-//! | ----------------------------- | --- | ------ | ----- | ---- | ----- |
-//! | `&self` receiver              | ✔   | ✔      | ✔     | ✔    | ✔     |
-//! | `&mut self` receiver          | ✗   | ✔      | ✔     | ✗    | ✗     |
-//! | `self` receiver               | ✗   | ✗      | ✔     | ✗    | ✗     |
-//! | no `self` receiver            | ✔   | ✔      | ✔     | ✔    | ✔     |
+//! | Trait contains method with...     | `&` | `&mut` | `Box` | `Rc` | `Arc` | `Cow` | `Pin` |
+//! | --------------------------------- | --- | ------ | ----- | ---- | ----- | ----- | ----- |
+//! | `&self` receiver                  | ✔   | ✔      | ✔     | ✔    | ✔     | ✔     | ✔     |
+//! | `&mut self` receiver              | ✗   | ✔      | ✔     | ✗    | ✗     | ✗     | ✗     |
+//! | `self` receiver                   | ✗   | ✗      | ✔     | ✗    | ✗     | ✗     | ✗     |
+//! | `self: Pin<&Self>` receiver       | ✔¹  | ✔¹     | ✔¹    | ✔¹   | ✔¹    | ✔¹    | ✔     |
+//! | `self: Pin<&mut Self>` receiver   | ✗   | ✔¹     | ✔¹    | ✗    | ✗     | ✗     | ✔²    |
+//! | `self: Box<Self>` receiver        | ✗   | ✗      | ✔     | ✗    | ✗     | ✗     | ✗     |
+//! | `self: Rc<Self>` receiver         | ✗   | ✗      | ✗     | ✔    | ✗     | ✗     | ✗     |
+//! | `self: Arc<Self>` receiver        | ✗   | ✗      | ✗     | ✗    | ✔     | ✗     | ✗     |
+//! | no `self` receiver                | ✔   | ✔      | ✔     | ✔    | ✔     | ✔     | ✔     |
+//!
+//! ¹ Only if `T: Unpin`, which is then added as bound to the generated impl.
+//!
+//! ² Only if `P: DerefMut + Unpin`, which is true for `Pin<&mut T>` and
+//! `Pin<Box<T>>`, for example.
+//!
+//! Receivers with an explicit type like `self: &Self` or `self: &mut Self` are
+//! treated exactly like their short forms `&self` and `&mut self`.
+//!
+//! Arguments other than the receiver can use `Self` as well (e.g.
+//! `fn merge(&self, other: &Self)`). In the generated impl, such an argument
+//! has the proxy type (e.g. `&&T` for the `&` proxy) and is converted to the
+//! proxied type before it's passed on:
+//!
+//! | Argument type                     | `&` | `&mut` | `Box` | `Rc` | `Arc` | `Cow` | `Pin` |
+//! | --------------------------------- | --- | ------ | ----- | ---- | ----- | ----- | ----- |
+//! | `&Self` or `Option<&Self>`        | ✔   | ✔      | ✔     | ✔    | ✔     | ✔     | ✔     |
+//! | `&mut Self` or `Option<&mut Self>`| ✗   | ✔      | ✔     | ✗    | ✗     | ✗     | ✗     |
+//! | `Self` or `Option<Self>`          | ✗   | ✗      | ✔     | ✗    | ✗     | ✗     | ✗     |
+//!
+//! Other argument types mentioning `Self` (like `&[Self]`) cannot be
+//! converted and lead to an error. Paths like `Self::Item` are not affected
+//! by this.
+//!
+//! Similarly, methods can return `Self`, `Option<Self>` or `Result<Self, E>`
+//! (e.g. constructors like `fn new(cfg: Config) -> Self`). The value returned
+//! by the proxied type is then wrapped with `Box::new`, `Rc::new`,
+//! `Arc::new` or the constructor of the wrapper type. This is not possible
+//! for `&`, `&mut`, `Cow` and `Pin`, so traits with such methods cannot be
+//! implemented for those proxy types.
+//!
+//! Builder-style methods returning `&Self` or `&mut Self` (e.g.
+//! `fn set_timeout(&mut self, d: Duration) -> &mut Self`) are supported for
+//! all references and smart pointers that allow the method's receiver. The
+//! generated method calls the method of the proxied type, discards the
+//! returned reference and returns `self` instead. That's only done if the
+//! returned reference can't be anything but the receiver: no other argument
+//! may mention `Self` and the lifetime of the returned reference has to be
+//! elided or the one of the receiver. Methods like
+//! `fn larger<'a>(&'a self, other: &'a Self) -> &'a Self` are rejected.
 //!
 //! References and smart pointers have **no restriction in regard to associated
 //! types and associated consts**! Meaning: traits with associated types/consts
@@ -176,6 +247,304 @@
 //! coherence and orphan rules that can emerge due to this impl.
 //!
 //!
+//! # Enum dispatch
+//!
+//! `enum <name>(<types>)` generates an enum with one variant per type, a
+//! `From` impl for each variant and an impl of the trait that dispatches
+//! each method to the current variant with a `match`. This allows static
+//! dispatch over a closed set of implementations without `Box<dyn Trait>`:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! #[auto_impl(enum AnyStore(MemStore, DiskStore))]
+//! trait Store {
+//!     fn get(&self, key: &str) -> Option<String>;
+//! }
+//!
+//! struct MemStore;
+//! impl Store for MemStore {
+//!     fn get(&self, key: &str) -> Option<String> { None }
+//! }
+//!
+//! struct DiskStore;
+//! impl Store for DiskStore {
+//!     fn get(&self, key: &str) -> Option<String> { None }
+//! }
+//!
+//! let store: AnyStore = MemStore.into();
+//! assert!(matches!(store, AnyStore::MemStore(_)));
+//! ```
+//!
+//! The variants are named after the last path segment of their type and the
+//! enum has the same visibility as the trait. Methods returning `Self` wrap
+//! the returned value in the variant it came from. Associated types are
+//! taken from the first variant; all other variants must have the same
+//! associated types. Methods without receiver, `Box`, `Rc` or `Arc`
+//! receivers and associated consts are not supported.
+//!
+//! In `keep_default_for`, enums are referred to by their name only, e.g.
+//! `keep_default_for(enum AnyStore)`.
+//!
+//!
+//! # Delegating to a field
+//!
+//! `delegate(<type> => self.<field>)` implements the trait for a local
+//! struct by forwarding each method to one of its fields. Named fields and
+//! tuple fields (`self.0`) are supported:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! #[auto_impl(delegate(Instrumented => self.inner))]
+//! trait Store {
+//!     fn get(&self, key: &str) -> Option<String>;
+//!
+//!     #[auto_impl(keep_default_for(delegate(Instrumented)))]
+//!     fn describe(&self) -> String {
+//!         "store".into()
+//!     }
+//! }
+//!
+//! struct MemStore;
+//! impl Store for MemStore {
+//!     fn get(&self, key: &str) -> Option<String> { None }
+//! }
+//!
+//! struct Instrumented {
+//!     inner: MemStore,
+//!     calls: u32,
+//! }
+//! ```
+//!
+//! The methods are forwarded to the field itself, not to the value it points
+//! to, so the type of the field has to implement the trait. For a field of
+//! type `Box<dyn Store>`, list `Box` as well:
+//! `#[auto_impl(Box, delegate(Instrumented => self.inner))]`. The type of
+//! the field is inferred from the receiver, so associated types and consts as well as methods
+//! without receiver can only be forwarded if it's given explicitly, like
+//! `delegate(Instrumented => self.inner: MemStore)`. Methods with pinned
+//! receivers (`self: Pin<&Self>` or `self: Pin<&mut Self>`) pin the field
+//! again, so it has to be `Unpin`. Methods returning `Self` and `Box`, `Rc`
+//! or `Arc` receivers are not supported.
+//!
+//! In `keep_default_for`, delegating structs are referred to by their type
+//! only, e.g. `keep_default_for(delegate(Instrumented))`. Such methods keep
+//! their default implementation, which can call inherent methods of the
+//! struct to customize the behaviour.
+//!
+//!
+//! # Delegating traits of other crates (`#[derive(Delegate)]`)
+//!
+//! `delegate(...)` only works for structs the trait's crate knows about. To
+//! let other crates delegate to a trait, list `export_descriptor` in the
+//! attribute. This emits a hidden `#[macro_export]` macro (the *descriptor*)
+//! that replays the definition of the trait, which `#[derive(Delegate)]`
+//! uses to implement it:
+//!
+//! ```ignore
+//! // In the crate `my_core`:
+//! pub mod store {
+//!     #[auto_impl(&, Box, export_descriptor)]
+//!     pub trait Store {
+//!         fn get(&self, key: &str) -> Option<String>;
+//!     }
+//! }
+//!
+//! // In another crate:
+//! use auto_impl::Delegate;
+//!
+//! #[derive(Delegate)]
+//! #[delegate(my_core::store::Store, to = inner)]
+//! struct Instrumented {
+//!     inner: my_core::store::MemStore,
+//!     calls: u32,
+//! }
+//!
+//! #[derive(Delegate)]
+//! #[delegate(my_core::store::Store)]
+//! enum AnyStore {
+//!     Mem(my_core::store::MemStore),
+//!     Instrumented(Instrumented),
+//! }
+//! ```
+//!
+//! Structs forward to their only field or the one given with `to = <field>`
+//! like `delegate(...)` does, enums forward to the single field of each
+//! variant like `enum ...` does. Generic structs are supported, generic
+//! enums are not. A complete example can be found in `examples/delegate`.
+//!
+//! There are some limitations, as the trait definition is replayed in the
+//! other crate:
+//!
+//! - The descriptor is exported at the root of the trait's crate and
+//!   re-exported next to the trait, so the path in `#[delegate(...)]` has
+//!   to lead to the module defining the trait (or one glob-importing it).
+//!   At the root, the descriptor is named after the trait, so two exported
+//!   traits with the same name in one crate conflict. Give one of them
+//!   another name with `export_descriptor(name = <name>)`, e.g.
+//!   `#[auto_impl(&, export_descriptor(name = StoreV2))]`.
+//! - Types used in the methods of the trait have to be in scope where
+//!   `#[derive(Delegate)]` is used.
+//! - `auto_impl` has to be a dependency of that crate under its own name.
+//!
+//!
+//! # Impl blocks of foreign traits
+//!
+//! The trait definition is not available for traits of other crates. In
+//! that case, the attribute can be put on the impl block of your type
+//! instead, which then also serves as the trait definition:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! struct Bag(Vec<u32>);
+//!
+//! #[auto_impl(&mut, Box)]
+//! impl Extend<u32> for Bag {
+//!     fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+//!         self.0.extend(iter);
+//!     }
+//! }
+//! ```
+//!
+//! This generates `impl Extend<u32> for &mut Bag` and
+//! `impl Extend<u32> for Box<Bag>`. Only `&`, `&mut`, `Box`, `Rc` and `Arc`
+//! are supported here, and the orphan rules only allow `&`, `&mut` and `Box`
+//! for traits of other crates. Methods the impl block doesn't override are
+//! not forwarded, and `keep_default_for` on a method uses the default
+//! implementation of the trait for the listed proxy types. Use `Self` in the
+//! signatures instead of the type's name, as the methods are replayed for
+//! each proxy type.
+//!
+//!
+//! # Modules
+//!
+//! To give many traits the same proxy types, the attribute can be put on an
+//! inline module. It then applies to every trait directly inside of it:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! #[auto_impl(&, Box)]
+//! mod traits {
+//!     pub trait Greet {
+//!         fn greet(&self) -> String;
+//!     }
+//!
+//!     // Overrides the proxy types of the module.
+//!     #[auto_impl(&mut, Box)]
+//!     pub trait Reset {
+//!         fn reset(&mut self);
+//!     }
+//!
+//!     // Not auto-implemented at all.
+//!     #[auto_impl(skip)]
+//!     pub trait Named {
+//!         fn name(&self) -> &'static str;
+//!     }
+//! }
+//! ```
+//!
+//! Further `#[auto_impl]` attributes on a trait add proxy types, like on
+//! traits outside of modules (see below). Attributes in an enabled
+//! `cfg_attr` count as well, and `auto_impl` doesn't have to be in scope
+//! inside the module.
+//!
+//! If a trait without its own attribute is incompatible with some of the
+//! proxy types of the module, it is not implemented for them and a warning
+//! (shown as a deprecation warning) names the skipped proxy types. `enum`
+//! proxy types cannot be used on modules.
+//!
+//!
+//! # Multiple attributes
+//!
+//! A trait can have multiple `#[auto_impl]` attributes, which is useful to
+//! generate some impls only if a feature is enabled:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! #[auto_impl(&, &mut)]
+//! #[cfg_attr(feature = "alloc", auto_impl(Box, Arc))]
+//! trait Foo {
+//!     fn foo(&self);
+//! }
+//! ```
+//!
+//! Attributes on methods like `keep_default_for` apply to all of them. A
+//! proxy type listed in more than one of the attributes is an error.
+//!
+//!
+//! # Extra bounds
+//!
+//! An impl can be restricted with an additional bound after `where`, in which
+//! `T` refers to the proxied type:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! #[auto_impl(&, Box where T: Clone, Arc where T: Send + Sync)]
+//! trait Worker {
+//!     fn work(&self);
+//! }
+//! ```
+//!
+//! This generates `impl<T: Worker> Worker for Arc<T> where T: Send + Sync`
+//! and so on. `T` is replaced wherever it starts a path, e.g. also in
+//! `T::Item: Send` or `Vec<T>: Clone`. If the trait itself has a type
+//! parameter named `T`, using `T` in a bound is an error, as it would be
+//! unclear which type is meant; rename the parameter of the trait in that
+//! case. Only one predicate can be listed after `where`, as the comma
+//! separates the proxy types. Bounds after `unsafe(...)` apply to all types
+//! in it. Enums, tuples and delegating structs have no proxied type and
+//! don't support bounds.
+//!
+//!
+//! # Custom proxy types
+//!
+//! Pointer types that are not built into Rust (like `triomphe::Arc` or
+//! `bumpalo::boxed::Box<'a, T>`) can be used as proxy types, too. They are
+//! declared as `custom(<type>, <capabilities>)`. `<type>` is the pointer type
+//! with the placeholder `T` for the proxied type. Lifetimes used in it are
+//! added as parameters to the generated impl. `<capabilities>` lists what the
+//! pointer can do and thus which receivers can be forwarded:
+//!
+//! | Capability   | Requirement                             | Receivers |
+//! | ------------ | --------------------------------------- | --------- |
+//! | `deref`      | `Ptr<T>: Deref<Target = T>`             | `&self`, `self: Pin<&Self>`¹ |
+//! | `deref_mut`  | `Ptr<T>: DerefMut<Target = T>`          | `&self`, `&mut self`, `self: Pin<&mut Self>`¹, ... |
+//! | `into_inner` | an associated `fn into_inner(Self) -> T` | `self` |
+//!
+//! ¹ Only if `T: Unpin`, which is then added as bound to the generated impl.
+//!
+//! The `Deref`/`DerefMut` requirements are added as bounds to the generated
+//! impl. As auto_impl cannot know whether the pointer supports unsized types,
+//! the generated impl always requires `T: Sized`.
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! # use std::ops::{Deref, DerefMut};
+//! struct MyBox<T>(T);
+//!
+//! impl<T> MyBox<T> {
+//!     fn into_inner(self) -> T {
+//!         self.0
+//!     }
+//! }
+//!
+//! # impl<T> Deref for MyBox<T> {
+//! #     type Target = T;
+//! #     fn deref(&self) -> &T { &self.0 }
+//! # }
+//! # impl<T> DerefMut for MyBox<T> {
+//! #     fn deref_mut(&mut self) -> &mut T { &mut self.0 }
+//! # }
+//! #[auto_impl(&mut, custom(MyBox<T>, deref_mut, into_inner))]
+//! trait Counter {
+//!     fn get(&self) -> u32;
+//!     fn increment(&mut self);
+//! }
+//! ```
+//!
+//! In `keep_default_for`, custom proxy types are referred to by their type
+//! only, e.g. `keep_default_for(custom(MyBox<T>))`.
+//!
+//!
 //! # The `keep_default_for` attribute for methods
 //!
 //! By default, the impls generated by `auto_impl` will overwrite all methods
@@ -202,6 +571,96 @@
 //!     }
 //! }
 //! ```
+//!
+//!
+//! # The `Option` proxy
+//!
+//! The impl for `Option<T>` forwards each method to the value in `Some`. If
+//! the `Option` is `None`, methods returning `()` do nothing, builder methods
+//! returning `&Self` or `&mut Self` return `self`, methods returning `Self`
+//! return `None` and all other methods return `Default::default()`. This can
+//! be overridden with the `#[auto_impl(on_none = <expr>)]` attribute, which is
+//! required for return types that cannot implement `Default`, like references
+//! or `Result`:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! #[auto_impl(Option)]
+//! trait Plugin {
+//!     fn on_start(&mut self);
+//!     fn priority(&self) -> u32;
+//!
+//!     #[auto_impl(on_none = "none")]
+//!     fn name(&self) -> &str;
+//! }
+//! ```
+//!
+//! The receivers `self: Box<Self>`, `self: Rc<Self>` and `self: Arc<Self>`
+//! and arguments of type `Self` (other than the receiver) are not supported.
+//!
+//!
+//! # Fan-out proxies (`Vec`, `slice`, `array` and `tuples`)
+//!
+//! The impls for collections and tuples call each method on every element,
+//! in order. This is useful for observer or listener traits. Methods returning `()` (or
+//! `&Self`/`&mut Self`, which return `self`) need nothing else. For all other
+//! methods, you have to specify how the results are combined with the
+//! `#[auto_impl(fan_out = ...)]` attribute:
+//!
+//! | Reduction    | Return type  | Result |
+//! | ------------ | ------------ | ------ |
+//! | `first_some` | `Option<_>`  | the first `Some` (later elements are not called) |
+//! | `all`        | `bool`       | whether all elements return `true` (stops at the first `false`) |
+//! | `any`        | `bool`       | whether any element returns `true` (stops at the first `true`) |
+//! | `collect`    | a collection | the items of all results collected into one collection |
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! # pub struct Event;
+//! #[auto_impl(Vec, slice, array)]
+//! trait Listener {
+//!     fn on_event(&mut self, e: &Event);
+//!
+//!     #[auto_impl(fan_out = any)]
+//!     fn is_interested(&self, e: &Event) -> bool;
+//! }
+//! ```
+//!
+//! The arguments are passed to every call, so they have to be `Copy` (e.g.
+//! references). An argument that isn't leads to an error pointing to it.
+//! Methods with a `self` receiver consume the elements (this is
+//! not possible for slices). Pinned receivers and arguments of type `Self`
+//! are not supported. Methods without receiver are forwarded to the element
+//! type.
+//!
+//! `tuples(<min>..=<max>)` generates one impl for each tuple size in the
+//! range, e.g. `tuples(1..=12)` implements the trait for `(A,)` up to
+//! `(A, B, ..., L)` where each element implements the trait. As the elements
+//! have different types, traits with associated types, associated consts or
+//! methods without receiver cannot be implemented for tuples.
+//!
+//!
+//! # Unsafe traits
+//!
+//! Implementing an `unsafe trait` for a proxy type means asserting that the
+//! proxy type upholds the safety contract of the trait whenever the proxied
+//! type does. As `auto_impl` cannot check that, it refuses to implement an
+//! `unsafe trait` unless you opt in by listing the proxy types in
+//! `unsafe(...)`. The generated impls are then `unsafe impl`s:
+//!
+//! ```
+//! # use auto_impl::auto_impl;
+//! /// # Safety
+//! ///
+//! /// `len` must never return more than the number of valid bytes at `ptr`.
+//! #[auto_impl(unsafe(&, &mut, Box))]
+//! unsafe trait Buffer {
+//!     fn ptr(&self) -> *const u8;
+//!     fn len(&self) -> usize;
+//! }
+//! ```
+//!
+//! Using `unsafe(...)` for a trait that is not unsafe is an error.
 
 extern crate proc_macro;
 #[macro_use]
@@ -211,7 +670,9 @@ use proc_macro::TokenStream;
 
 mod analyze;
 mod attr;
+mod delegate;
 mod gen;
+mod module;
 mod proxy;
 
 /// See crate documentation for more information.
@@ -229,15 +690,139 @@ fn auto_impl2(
 ) -> syn::Result<proc_macro2::TokenStream> {
     // Try to parse the token stream from the attribute to get a list of proxy
     // types.
-    let proxy_types = proxy::parse_types(args);
+    let raw_args = proc_macro2::TokenStream::from(args.clone());
+    let args = proxy::parse_args(args);
+
+    // Impl blocks `impl Trait for Type` and modules are handled separately.
+    match syn::parse2::<syn::Item>(input.clone()) {
+        Ok(syn::Item::Impl(impl_block)) => return Ok(auto_impl_for_impl_block(args, impl_block)),
+        Ok(syn::Item::Mod(module)) => return module::auto_impl_for_mod(args, module),
+        _ => {}
+    }
 
     let mut trait_def = syn::parse2::<syn::ItemTrait>(input)?;
+    let args = check_listed_proxies(args, raw_args, &mut trait_def);
+    Ok(auto_impl_for_trait(args, trait_def))
+}
 
-    let generated = gen::gen_impls(&proxy_types, &trait_def)?;
+/// Checks the arguments of an `#[auto_impl]` invocation on a trait against
+/// those of the earlier ones and returns them.
+///
+/// The trait can have multiple `#[auto_impl]` attributes (e.g. one in
+/// `cfg_attr`). Each invocation records its proxy types in a hidden
+/// attribute for the next one, which rejects proxy types listed twice.
+fn check_listed_proxies(
+    args: syn::Result<proxy::Args>,
+    raw_args: proc_macro2::TokenStream,
+    trait_def: &mut syn::ItemTrait,
+) -> syn::Result<proxy::Args> {
+    // The list is empty after `#[auto_impl(skip)]` on a trait in a module.
+    let listed = attr::take_listed_proxies(trait_def).filter(|listed| !listed.is_empty());
+    let args = match &listed {
+        Some(listed) => proxy::parse_args(quote!(#listed, #raw_args).into()).and(args),
+        None => args,
+    };
+    if trait_def.attrs.iter().any(attr::is_our_attr) {
+        let listed = match (&args, listed) {
+            (Ok(_), Some(listed)) => quote!(#listed, #raw_args),
+            (Ok(_), None) => raw_args,
+            (Err(_), listed) => listed.unwrap_or_default(),
+        };
+        trait_def.attrs.push(attr::listed_proxies_attr(listed));
+    }
+
+    args
+}
+
+/// Generates the impls for the given trait and returns them together with
+/// the trait definition.
+fn auto_impl_for_trait(
+    args: syn::Result<proxy::Args>,
+    mut trait_def: syn::ItemTrait,
+) -> proc_macro2::TokenStream {
+    // From here on, the trait definition is always emitted, even if errors
+    // occur. Otherwise every use of the trait would lead to another error,
+    // hiding the actual problem.
+    let generated = gen_for_trait(args, &trait_def);
 
     // Before returning the trait definition, we have to remove all
-    // `#[auto_impl(...)]` attributes on all methods.
-    attr::remove_our_attrs(&mut trait_def)?;
+    // `#[auto_impl(...)]` attributes on all methods. If another
+    // `#[auto_impl]` attribute on the trait is still to be expanded, they are
+    // kept for it.
+    let attr_errors = if trait_def.attrs.iter().any(attr::is_our_attr) {
+        proc_macro2::TokenStream::new()
+    } else {
+        match attr::remove_our_attrs(&mut trait_def) {
+            Ok(()) => proc_macro2::TokenStream::new(),
+            Err(e) => e.into_compile_error(),
+        }
+    };
+
+    quote!(#trait_def #generated #attr_errors)
+}
+
+/// Generates the impls (and the descriptor) for the given trait, or the
+/// errors of the arguments.
+fn gen_for_trait(
+    args: syn::Result<proxy::Args>,
+    trait_def: &syn::ItemTrait,
+) -> proc_macro2::TokenStream {
+    match args {
+        Ok(args) => {
+            let mut generated = gen::gen_impls(&args.proxies, trait_def, true);
+            if args.export_descriptor {
+                generated.extend(delegate::gen_descriptor(
+                    trait_def,
+                    args.descriptor_name.as_ref(),
+                ));
+            }
+            generated
+        }
+        Err(e) => e.into_compile_error(),
+    }
+}
+
+fn auto_impl_for_impl_block(
+    args: syn::Result<proxy::Args>,
+    mut impl_block: syn::ItemImpl,
+) -> proc_macro2::TokenStream {
+    // Like the trait definition, the impl block is always emitted.
+    let generated = match args {
+        Ok(args) if args.export_descriptor => syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`export_descriptor` can only be used on trait definitions",
+        )
+        .into_compile_error(),
+        Ok(args) => gen::gen_impl_block_impls(&args.proxies, &impl_block),
+        Err(e) => e.into_compile_error(),
+    };
+
+    let attr_errors = match attr::remove_our_impl_attrs(&mut impl_block) {
+        Ok(()) => proc_macro2::TokenStream::new(),
+        Err(e) => e.into_compile_error(),
+    };
 
-    Ok(quote!(#trait_def #generated))
+    quote!(#impl_block #generated #attr_errors)
+}
+
+/// Implements traits exported with `#[auto_impl(export_descriptor)]` by
+/// delegating to a field of a struct or the variants of an enum. See crate
+/// documentation for more information.
+#[proc_macro_derive(Delegate, attributes(delegate))]
+pub fn derive_delegate(input: TokenStream) -> TokenStream {
+    match delegate::derive_delegate(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// Invoked by the descriptor macros emitted for
+/// `#[auto_impl(export_descriptor)]`. Not public API.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __delegate_impl(input: TokenStream) -> TokenStream {
+    match delegate::delegate_impl(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
 }
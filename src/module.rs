@@ -0,0 +1,261 @@
+//! `#[auto_impl]` on inline modules, which applies the proxy types to every
+//! trait in the module.
+
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+    parse_quote, punctuated::Punctuated, spanned::Spanned, Attribute, Error, Ident, Item, ItemMod,
+    ItemTrait, Meta, Token,
+};
+
+use crate::{
+    attr::is_our_attr,
+    auto_impl_for_trait, check_listed_proxies,
+    gen::is_compatible,
+    gen_for_trait,
+    proxy::{parse_args, Args, Proxy, ProxyType},
+};
+
+/// Generates the impls for all traits directly inside the given module and
+/// returns them together with the module.
+///
+/// Traits can have an `#[auto_impl(...)]` attribute of their own, which
+/// replaces the proxy types of the module, or `#[auto_impl(skip)]`. Further
+/// attributes add proxy types (see `auto_impl_for_mod_trait`). Proxy
+/// types of the module a trait is incompatible with are skipped for that
+/// trait, with a warning (see `gen_skip_note`).
+pub(crate) fn auto_impl_for_mod(
+    args: syn::Result<Args>,
+    mut module: ItemMod,
+) -> syn::Result<TokenStream2> {
+    let items = match &mut module.content {
+        Some((_, items)) => items,
+        None => {
+            return Err(Error::new(
+                module.span(),
+                "`#[auto_impl]` can only be used on inline modules (`mod name { ... }`)",
+            ));
+        }
+    };
+
+    // Like the trait definitions, the module is always emitted.
+    let mut errors = Vec::new();
+    let args = match args {
+        Ok(args) => Some(args),
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    };
+    if let Some(args) = &args {
+        if let Some(name) = &args.descriptor_name {
+            errors.push(Error::new(
+                name.span(),
+                "`export_descriptor(name = ...)` cannot be used on modules, as all traits would \
+                use the same name; use it on the trait instead",
+            ));
+        }
+        for proxy in &args.proxies {
+            if let ProxyType::Enum(e) = &proxy.ty {
+                errors.push(Error::new(
+                    proxy.span,
+                    format_args!(
+                        "`enum {}(...)` cannot be used on modules, as each trait would define \
+                        the enum; use it on the trait instead",
+                        e.ident,
+                    ),
+                ));
+            }
+        }
+    }
+
+    for item in items.iter_mut() {
+        if let Item::Trait(trait_def) = item {
+            let tokens = auto_impl_for_mod_trait(args.as_ref(), trait_def.clone());
+            *item = Item::Verbatim(tokens);
+        }
+    }
+
+    let errors = errors.into_iter().map(Error::into_compile_error);
+    Ok(quote!(#module #(#errors)*))
+}
+
+/// Generates the impls for a trait in a module with the given arguments and
+/// returns them together with the trait definition.
+///
+/// The `#[auto_impl]` attributes of the trait are expanded here, as the
+/// attribute might not be in scope inside the module. The first one replaces
+/// the proxy types of the module, further ones are checked against the
+/// earlier ones like on traits outside of modules (see
+/// `check_listed_proxies`).
+fn auto_impl_for_mod_trait(module_args: Option<&Args>, mut trait_def: ItemTrait) -> TokenStream2 {
+    // `cfg_attr` is not expanded yet, so the trait is emitted twice: with the
+    // attributes in it and without.
+    if let Some((pos, predicate, attrs)) = find_our_cfg_attr(&trait_def) {
+        let mut enabled = trait_def.clone();
+        enabled.attrs.splice(pos..=pos, attrs);
+        trait_def.attrs.remove(pos);
+
+        let enabled = with_cfg(
+            &quote!(#predicate),
+            auto_impl_for_mod_trait(module_args, enabled),
+        );
+        let disabled = with_cfg(
+            &quote!(not(#predicate)),
+            auto_impl_for_mod_trait(module_args, trait_def),
+        );
+        return quote!(#enabled #disabled);
+    }
+
+    let mut note = TokenStream2::new();
+    let (mut args, mut raw_args) = match take_our_attr(&mut trait_def) {
+        Some(attr) => match attr.meta.require_list() {
+            Ok(list) if is_skip(&list.tokens) => (Ok(no_args()), TokenStream2::new()),
+            _ => attr_args(&attr),
+        },
+        None => {
+            let (args, skipped) = match module_args {
+                Some(args) => compatible_args(args, &trait_def),
+                None => (no_args(), Vec::new()),
+            };
+            if !skipped.is_empty() {
+                note = gen_skip_note(&trait_def, &skipped);
+            }
+            (Ok(args), TokenStream2::new())
+        }
+    };
+
+    let mut generated = TokenStream2::new();
+    let args = loop {
+        let checked = check_listed_proxies(args, raw_args, &mut trait_def);
+        let attr = match take_our_attr(&mut trait_def) {
+            Some(attr) => attr,
+            None => break checked,
+        };
+
+        generated.extend(gen_for_trait(checked, &trait_def));
+        let next = attr_args(&attr);
+        args = next.0;
+        raw_args = next.1;
+    };
+
+    let tokens = auto_impl_for_trait(args, trait_def);
+    quote!(#generated #tokens #note)
+}
+
+/// Removes the first `#[auto_impl]` attribute from the trait and returns it.
+/// The hidden attribute with the proxy types of the earlier attributes is
+/// always the last one, so it's only returned if there is no other one.
+fn take_our_attr(trait_def: &mut ItemTrait) -> Option<Attribute> {
+    let pos = trait_def.attrs.iter().position(is_our_attr)?;
+    Some(trait_def.attrs.remove(pos))
+}
+
+/// Parses the arguments of the given `#[auto_impl]` attribute and returns
+/// them together with their tokens.
+fn attr_args(attr: &Attribute) -> (syn::Result<Args>, TokenStream2) {
+    match attr.meta.require_list() {
+        Ok(list) => (parse_args(list.tokens.clone().into()), list.tokens.clone()),
+        Err(e) => (Err(e), TokenStream2::new()),
+    }
+}
+
+/// Finds the first `cfg_attr` on the trait that contains an `#[auto_impl]`
+/// attribute. Returns its position, its predicate and the attributes in it.
+fn find_our_cfg_attr(trait_def: &ItemTrait) -> Option<(usize, Meta, Vec<Attribute>)> {
+    trait_def.attrs.iter().enumerate().find_map(|(pos, attr)| {
+        split_cfg_attr(attr).map(|(predicate, attrs)| (pos, predicate, attrs))
+    })
+}
+
+/// Splits `#[cfg_attr(<predicate>, <attributes>)]` into the predicate and
+/// the attributes if one of them is (or contains) an `#[auto_impl]`
+/// attribute.
+fn split_cfg_attr(attr: &Attribute) -> Option<(Meta, Vec<Attribute>)> {
+    if !attr.path().is_ident("cfg_attr") {
+        return None;
+    }
+
+    let mut metas = attr
+        .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        .ok()?
+        .into_iter();
+    let predicate = metas.next()?;
+    let attrs = metas
+        .map(|meta| parse_quote!(#[#meta]))
+        .collect::<Vec<Attribute>>();
+    if !attrs
+        .iter()
+        .any(|a| is_our_attr(a) || split_cfg_attr(a).is_some())
+    {
+        return None;
+    }
+    Some((predicate, attrs))
+}
+
+/// Adds `#[cfg(<predicate>)]` to all the given items.
+fn with_cfg(predicate: &TokenStream2, tokens: TokenStream2) -> TokenStream2 {
+    match syn::parse2::<syn::File>(tokens.clone()) {
+        Ok(file) => {
+            let items = file.items;
+            quote!(#(#[cfg(#predicate)] #items)*)
+        }
+        Err(_) => tokens,
+    }
+}
+
+/// Returns the arguments of the module without the proxy types the trait is
+/// incompatible with, which are returned separately.
+fn compatible_args(args: &Args, trait_def: &ItemTrait) -> (Args, Vec<Proxy>) {
+    let (proxies, skipped) = args
+        .proxies
+        .iter()
+        .filter(|proxy| !matches!(proxy.ty, ProxyType::Enum(_)))
+        .cloned()
+        .partition(|proxy| is_compatible(&proxy.ty, trait_def));
+
+    let args = Args {
+        proxies,
+        export_descriptor: args.export_descriptor,
+        descriptor_name: None,
+    };
+    (args, skipped)
+}
+
+fn no_args() -> Args {
+    Args {
+        proxies: Vec::new(),
+        export_descriptor: false,
+        descriptor_name: None,
+    }
+}
+
+/// Checks if the arguments of an attribute are just `skip`.
+fn is_skip(tokens: &TokenStream2) -> bool {
+    syn::parse2::<Ident>(tokens.clone()).map_or(false, |ident| ident == "skip")
+}
+
+/// Generates a warning that the trait is not implemented for the given proxy
+/// types. Proc macros cannot emit warnings on stable Rust, so this uses a
+/// deprecated item, which makes the compiler print our note.
+fn gen_skip_note(trait_def: &ItemTrait, skipped: &[Proxy]) -> TokenStream2 {
+    let names = skipped
+        .iter()
+        .map(|proxy| format!("`{}`", proxy.ty.name()))
+        .collect::<Vec<_>>();
+    let note = format!(
+        "the trait `{}` is not auto-implemented for {}, as it is incompatible with them; choose \
+        the proxy types with `#[auto_impl(...)]` on the trait or skip it with \
+        `#[auto_impl(skip)]`",
+        trait_def.ident,
+        names.join(", "),
+    );
+
+    let span = trait_def.ident.span();
+    quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            struct AutoImplSkipped;
+            let _ = AutoImplSkipped;
+        };
+    }
+}
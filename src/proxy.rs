@@ -1,75 +1,796 @@
-use std::iter::Peekable;
-use syn::Error;
+use proc_macro2::{Span as Span2, TokenStream as TokenStream2};
+use quote::ToTokens;
+use std::{fmt, iter::Peekable, ops::RangeInclusive};
+use syn::{
+    parse::{ParseStream, Parser},
+    punctuated::Punctuated,
+    visit::{visit_lifetime, Visit},
+    visit_mut::{visit_path_mut, VisitMut},
+    Error, Generics, Ident, Lifetime, LitInt, Member, Path, Token, Type, WherePredicate,
+};
 
-use crate::proc_macro::{token_stream, TokenStream, TokenTree};
+use crate::proc_macro::{token_stream, Delimiter, Span, TokenStream, TokenTree};
 
 /// Types for which a trait can automatically be implemented.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum ProxyType {
     Ref,
     RefMut,
     Arc,
     Rc,
     Box,
+    Cow,
+    Pin,
+    ManuallyDrop,
+    AssertUnwindSafe,
+    Wrapping,
+    Reverse,
+    Option,
+    Vec,
+    Slice,
+    Array,
+    /// A tuple with the given number of elements. Listed as
+    /// `tuples(<min>..=<max>)` in the attribute, which results in one
+    /// `Tuple` per arity.
+    Tuple(usize),
+    /// An enum generated by us with one variant per type, declared as
+    /// `enum Name(<types>)` in the attribute.
+    Enum(Box<EnumProxy>),
+    /// A local struct that forwards to one of its fields, declared as
+    /// `delegate(<type> => self.<field>)` in the attribute.
+    Delegate(Box<DelegateProxy>),
     Fn,
     FnMut,
     FnOnce,
+    Custom(Box<CustomProxy>),
 }
 
 impl ProxyType {
+    /// All proxy types, in the order they are listed in the documentation.
+    pub(crate) const ALL: &'static [ProxyType] = &[
+        ProxyType::Ref,
+        ProxyType::RefMut,
+        ProxyType::Box,
+        ProxyType::Rc,
+        ProxyType::Arc,
+        ProxyType::Cow,
+        ProxyType::Pin,
+        ProxyType::ManuallyDrop,
+        ProxyType::AssertUnwindSafe,
+        ProxyType::Wrapping,
+        ProxyType::Reverse,
+        ProxyType::Option,
+        ProxyType::Vec,
+        ProxyType::Slice,
+        ProxyType::Array,
+        ProxyType::Fn,
+        ProxyType::FnMut,
+        ProxyType::FnOnce,
+    ];
+
     pub(crate) fn is_fn(&self) -> bool {
         matches!(*self, ProxyType::Fn | ProxyType::FnMut | ProxyType::FnOnce)
     }
+
+    /// Returns whether this is a collection or tuple that forwards each
+    /// method call to all of its elements.
+    pub(crate) fn is_fan_out(&self) -> bool {
+        matches!(
+            *self,
+            ProxyType::Vec | ProxyType::Slice | ProxyType::Array | ProxyType::Tuple(_)
+        )
+    }
+
+    /// Returns the abbreviation used for this proxy type in the attribute.
+    pub(crate) fn name(&self) -> String {
+        let name = match self {
+            ProxyType::Ref => "&",
+            ProxyType::RefMut => "&mut",
+            ProxyType::Arc => "Arc",
+            ProxyType::Rc => "Rc",
+            ProxyType::Box => "Box",
+            ProxyType::Cow => "Cow",
+            ProxyType::Pin => "Pin",
+            ProxyType::ManuallyDrop => "ManuallyDrop",
+            ProxyType::AssertUnwindSafe => "AssertUnwindSafe",
+            ProxyType::Wrapping => "Wrapping",
+            ProxyType::Reverse => "Reverse",
+            ProxyType::Option => "Option",
+            ProxyType::Vec => "Vec",
+            ProxyType::Slice => "slice",
+            ProxyType::Array => "array",
+            ProxyType::Tuple(arity) => return format!("tuples({})", arity),
+            ProxyType::Fn => "Fn",
+            ProxyType::FnMut => "FnMut",
+            ProxyType::FnOnce => "FnOnce",
+            ProxyType::Custom(custom) => return custom.name(),
+            ProxyType::Enum(e) => return format!("enum {}", e.ident),
+            ProxyType::Delegate(d) => return d.name(),
+        };
+
+        name.to_owned()
+    }
+}
+
+/// A user-defined pointer type, declared as `custom(<type>, <capabilities>)`
+/// in the attribute. The capabilities determine which receivers can be
+/// forwarded.
+#[derive(Clone)]
+pub(crate) struct CustomProxy {
+    /// The pointer type with the placeholder `T` for the proxied type, e.g.
+    /// `MyPtr<T>`.
+    pub(crate) ty: Type,
+
+    /// The pointer implements `Deref<Target = T>` (`deref`).
+    pub(crate) deref: bool,
+
+    /// The pointer implements `DerefMut<Target = T>` (`deref_mut`).
+    pub(crate) deref_mut: bool,
+
+    /// The pointer has an associated function `into_inner(Self) -> T`
+    /// (`into_inner`).
+    pub(crate) into_inner: bool,
+}
+
+impl CustomProxy {
+    /// The name of the placeholder for the proxied type.
+    const PLACEHOLDER: &'static str = "T";
+
+    /// Returns the pointer type with the placeholder replaced by the given
+    /// type parameter.
+    pub(crate) fn self_ty(&self, proxy_ty_param: &Ident) -> Type {
+        struct PlaceholderReplacer<'a>(&'a Ident);
+        impl VisitMut for PlaceholderReplacer<'_> {
+            fn visit_path_mut(&mut self, path: &mut Path) {
+                if path.is_ident(CustomProxy::PLACEHOLDER) {
+                    path.segments[0].ident = self.0.clone();
+                }
+                visit_path_mut(self, path);
+            }
+        }
+
+        let mut ty = self.ty.clone();
+        PlaceholderReplacer(proxy_ty_param).visit_type_mut(&mut ty);
+        ty
+    }
+
+    /// Returns all named lifetimes used in the pointer type. These have to be
+    /// declared as parameters of the generated impl.
+    pub(crate) fn lifetimes(&self) -> Vec<Lifetime> {
+        collect_lifetimes(&self.ty)
+    }
+
+    /// Returns whether the pointer type uses the placeholder `T` anywhere.
+    fn mentions_placeholder(&self) -> bool {
+        struct PlaceholderFinder(bool);
+        impl<'ast> Visit<'ast> for PlaceholderFinder {
+            fn visit_path(&mut self, path: &'ast Path) {
+                self.0 |= path.is_ident(CustomProxy::PLACEHOLDER);
+                syn::visit::visit_path(self, path);
+            }
+        }
+
+        let mut finder = PlaceholderFinder(false);
+        finder.visit_type(&self.ty);
+        finder.0
+    }
+
+    fn name(&self) -> String {
+        let mut out = format!("custom({}", self.ty.to_token_stream());
+        for (enabled, capability) in [
+            (self.deref, "deref"),
+            (self.deref_mut, "deref_mut"),
+            (self.into_inner, "into_inner"),
+        ] {
+            if enabled {
+                out.push_str(", ");
+                out.push_str(capability);
+            }
+        }
+        out.push(')');
+
+        humanize_type(&out)
+    }
+}
+
+/// Returns all named lifetimes used in the given type.
+fn collect_lifetimes(ty: &Type) -> Vec<Lifetime> {
+    struct LifetimeCollector(Vec<Lifetime>);
+    impl<'ast> Visit<'ast> for LifetimeCollector {
+        fn visit_lifetime(&mut self, lt: &'ast Lifetime) {
+            if lt.ident != "static" && lt.ident != "_" && !self.0.contains(lt) {
+                self.0.push(lt.clone());
+            }
+            visit_lifetime(self, lt);
+        }
+    }
+
+    let mut collector = LifetimeCollector(Vec::new());
+    collector.visit_type(ty);
+    collector.0
+}
+
+/// Makes a stringified token stream of a type look like it was written by a
+/// human.
+fn humanize_type(s: &str) -> String {
+    s.replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ::", "::")
+        .replace(":: ", "::")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+}
+
+// Two custom proxy types are the same if they have the same type (token-wise),
+// the capabilities don't matter. That way, `keep_default_for` can list just
+// the type.
+impl PartialEq for CustomProxy {
+    fn eq(&self, other: &Self) -> bool {
+        self.ty.to_token_stream().to_string() == other.ty.to_token_stream().to_string()
+    }
+}
+
+impl Eq for CustomProxy {}
+
+impl fmt::Debug for CustomProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// An enum that dispatches each method to its variants, declared as
+/// `enum Name(<types>)` in the attribute. The enum itself is generated as
+/// well.
+#[derive(Clone)]
+pub(crate) struct EnumProxy {
+    /// The name of the enum.
+    pub(crate) ident: Ident,
+
+    /// The variants of the enum. Each variant is named after the last path
+    /// segment of its type.
+    pub(crate) variants: Vec<(Ident, Type)>,
+
+    /// Whether the enum is defined by the user (with `#[derive(Delegate)]`)
+    /// instead of generated by us.
+    pub(crate) user_defined: bool,
+}
+
+// Like custom proxy types, enums are referred to by their name only in
+// `keep_default_for`.
+impl PartialEq for EnumProxy {
+    fn eq(&self, other: &Self) -> bool {
+        self.ident == other.ident
+    }
+}
+
+impl Eq for EnumProxy {}
+
+impl fmt::Debug for EnumProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "enum {}", self.ident)
+    }
+}
+
+/// A local struct that implements the trait by forwarding to one of its
+/// fields, declared as `delegate(<type> => self.<field>)` in the attribute.
+/// The type of the field can be given as `self.<field>: <type>`, which is
+/// required to forward associated items and methods without receiver.
+#[derive(Clone)]
+pub(crate) struct DelegateProxy {
+    /// The struct the trait is implemented for.
+    pub(crate) ty: Type,
+
+    /// The field the methods are forwarded to. Can only be omitted in
+    /// `keep_default_for`.
+    pub(crate) field: Option<Member>,
+
+    /// The type of the field, if given.
+    pub(crate) field_ty: Option<Type>,
+
+    /// The generics of the struct. These are only known (and non-empty) if
+    /// the struct uses `#[derive(Delegate)]`.
+    pub(crate) generics: Generics,
+}
+
+impl DelegateProxy {
+    /// Returns all named lifetimes used in the struct type. These have to be
+    /// declared as parameters of the generated impl.
+    pub(crate) fn lifetimes(&self) -> Vec<Lifetime> {
+        collect_lifetimes(&self.ty)
+    }
+
+    fn name(&self) -> String {
+        humanize_type(&format!("delegate({})", self.ty.to_token_stream()))
+    }
+}
+
+// Like custom proxy types, delegating structs are referred to by their type
+// only in `keep_default_for`.
+impl PartialEq for DelegateProxy {
+    fn eq(&self, other: &Self) -> bool {
+        self.ty.to_token_stream().to_string() == other.ty.to_token_stream().to_string()
+    }
+}
+
+impl Eq for DelegateProxy {}
+
+impl fmt::Debug for DelegateProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// A proxy type listed in the `#[auto_impl(...)]` attribute of a trait.
+#[derive(Debug, Clone)]
+pub(crate) struct Proxy {
+    pub(crate) ty: ProxyType,
+
+    /// The span of the proxy type in the attribute.
+    pub(crate) span: Span2,
+
+    /// Whether the type was listed in `unsafe(...)`, meaning that an
+    /// `unsafe impl` should be generated.
+    pub(crate) is_unsafe: bool,
+
+    /// Extra predicates for the impl, listed as `<type> where <predicate>`.
+    pub(crate) bounds: ExtraBounds,
+}
+
+/// Extra predicates for the impl of a proxy type, e.g. `T: Send + Sync` in
+/// `Arc where T: Send + Sync`. The placeholder `T` refers to the proxied
+/// type.
+#[derive(Clone, Default)]
+pub(crate) struct ExtraBounds(Vec<WherePredicate>);
+
+impl ExtraBounds {
+    /// The name of the placeholder for the proxied type.
+    const PLACEHOLDER: &'static str = "T";
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the predicates with the placeholder replaced by the given type
+    /// parameter.
+    pub(crate) fn predicates(&self, proxy_ty_param: &Ident) -> Vec<WherePredicate> {
+        struct PlaceholderReplacer<'a>(&'a Ident);
+        impl VisitMut for PlaceholderReplacer<'_> {
+            fn visit_path_mut(&mut self, path: &mut Path) {
+                // `T` as well as `T::Assoc`
+                if path.leading_colon.is_none()
+                    && path.segments[0].ident == ExtraBounds::PLACEHOLDER
+                {
+                    let mut ident = self.0.clone();
+                    ident.set_span(path.segments[0].ident.span());
+                    path.segments[0].ident = ident;
+                }
+                visit_path_mut(self, path);
+            }
+        }
+
+        let mut predicates = self.0.clone();
+        for predicate in &mut predicates {
+            PlaceholderReplacer(proxy_ty_param).visit_where_predicate_mut(predicate);
+        }
+        predicates
+    }
+
+    /// Returns an error if the placeholder is used although the trait has a
+    /// type parameter with the same name, as it would be unclear which one
+    /// is meant.
+    pub(crate) fn check_placeholder(&self, trait_generics: &Generics) -> syn::Result<()> {
+        struct PlaceholderFinder(Option<Span2>);
+        impl<'ast> Visit<'ast> for PlaceholderFinder {
+            fn visit_path(&mut self, path: &'ast Path) {
+                if self.0.is_none()
+                    && path.leading_colon.is_none()
+                    && path.segments[0].ident == ExtraBounds::PLACEHOLDER
+                {
+                    self.0 = Some(path.segments[0].ident.span());
+                }
+                syn::visit::visit_path(self, path);
+            }
+        }
+
+        if !trait_generics
+            .type_params()
+            .any(|param| param.ident == Self::PLACEHOLDER)
+        {
+            return Ok(());
+        }
+
+        let mut finder = PlaceholderFinder(None);
+        for predicate in &self.0 {
+            finder.visit_where_predicate(predicate);
+        }
+        match finder.0 {
+            Some(span) => Err(Error::new(
+                span,
+                format_args!(
+                    "`{0}` in `where` bounds refers to the proxied type, which is ambiguous \
+                    as the trait has a type parameter `{0}` as well; rename that parameter \
+                    to use `where` bounds",
+                    Self::PLACEHOLDER,
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for ExtraBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|p| p.to_token_stream().to_string()))
+            .finish()
+    }
+}
+
+type TokenIter = Peekable<token_stream::IntoIter>;
+
+/// The parsed arguments of the `#[auto_impl(...)]` attribute of a trait.
+#[derive(Debug)]
+pub(crate) struct Args {
+    pub(crate) proxies: Vec<Proxy>,
+
+    /// Whether `export_descriptor` was listed, meaning that a descriptor
+    /// macro for `#[derive(Delegate)]` should be emitted.
+    pub(crate) export_descriptor: bool,
+
+    /// The name given with `export_descriptor(name = <name>)`, which is used
+    /// instead of the name of the trait for the exported descriptor macro.
+    pub(crate) descriptor_name: Option<Ident>,
+}
+
+/// Parses the token stream of the attribute on the trait into a list of
+/// proxies.
+///
+/// In addition to the types accepted by `parse_types`, a comma-separated list
+/// of types can be wrapped in `unsafe(...)` to opt into generating
+/// `unsafe impl`s for an `unsafe trait`, and `export_descriptor` or
+/// `export_descriptor(name = <name>)` can be listed. Each entry can be
+/// followed by `where <predicate>` to add a bound to its impls.
+pub(crate) fn parse_args(args: TokenStream) -> syn::Result<Args> {
+    let mut export_descriptor = false;
+    let mut descriptor_name = None;
+    let (entries, mut error) = parse_list(args, |iter| {
+        let is_unsafe_next =
+            matches!(iter.peek(), Some(TokenTree::Ident(id)) if id.to_string() == "unsafe");
+        let export_descriptor_span = match iter.peek() {
+            Some(TokenTree::Ident(id)) if id.to_string() == "export_descriptor" => Some(id.span()),
+            _ => None,
+        };
+
+        if let Some(span) = export_descriptor_span {
+            let _ = iter.next();
+            if export_descriptor {
+                return Err(Error::new(span.into(), "duplicate `export_descriptor`"));
+            }
+            export_descriptor = true;
+            descriptor_name = eat_descriptor_name(iter)?;
+            Ok(Vec::new())
+        } else {
+            let types = if is_unsafe_next {
+                eat_unsafe_group(iter)?
+            } else {
+                eat_types(iter)?
+            };
+            let bounds = eat_extra_bounds(iter, &types)?;
+            Ok(types
+                .into_iter()
+                .map(|(ty, span)| (ty, span, is_unsafe_next, bounds.clone()))
+                .collect::<Vec<_>>())
+        }
+    });
+
+    let entries = entries.into_iter().flatten().collect::<Vec<_>>();
+    check_duplicates(
+        entries.iter().map(|(ty, span, ..)| (ty.clone(), *span)),
+        &mut error,
+    );
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(Args {
+            proxies: entries
+                .into_iter()
+                .map(|(ty, span, is_unsafe, bounds)| Proxy {
+                    ty,
+                    span: span.into(),
+                    is_unsafe,
+                    bounds,
+                })
+                .collect(),
+            export_descriptor,
+            descriptor_name,
+        }),
+    }
+}
+
+/// Parses the optional `(name = <name>)` after `export_descriptor`.
+fn eat_descriptor_name(iter: &mut TokenIter) -> syn::Result<Option<Ident>> {
+    let stream = match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            TokenStream2::from(group.stream())
+        }
+        _ => return Ok(None),
+    };
+    let _ = iter.next();
+
+    let parser = |input: ParseStream| {
+        let key = input.parse::<Ident>()?;
+        if key != "name" {
+            return Err(Error::new(
+                key.span(),
+                format_args!("unknown argument `{}`; expected `name = <name>`", key),
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        input.parse::<Ident>()
+    };
+    parser.parse2(stream).map(Some)
 }
 
 /// Parses the attribute token stream into a list of proxy types.
 ///
 /// The attribute token stream is the one in `#[auto_impl(...)]`. It is
 /// supposed to be a comma-separated list of possible proxy types. Legal values
-/// are `&`, `&mut`, `Box`, `Rc`, `Arc`, `Fn`, `FnMut` and `FnOnce`.
+/// are `&`, `&mut`, `Box`, `Rc`, `Arc`, `Cow`, `Pin`, `ManuallyDrop`,
+/// `AssertUnwindSafe`, `Wrapping`, `Reverse`, `Option`, `Vec`, `slice`,
+/// `array`, `Fn`, `FnMut`, `FnOnce`, tuples declared as
+/// `tuples(<min>..=<max>)`, enums declared as `enum <name>(<types>)`,
+/// delegating structs declared as `delegate(<type> => self.<field>)` and
+/// user-defined pointer types declared as `custom(<type>, <capabilities>)`.
+///
+/// If the given TokenStream is not valid, an error is returned that reports
+/// every invalid entry and every type that is listed more than once.
+pub(crate) fn parse_types(args: TokenStream) -> syn::Result<Vec<ProxyType>> {
+    let (entries, mut error) = parse_list(args, eat_types);
+    let entries = entries.into_iter().flatten().collect::<Vec<_>>();
+    check_duplicates(entries.iter().cloned(), &mut error);
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(entries.into_iter().map(|(ty, _)| ty).collect()),
+    }
+}
+
+/// Parses a comma-separated list (trailing commas are allowed) by calling
+/// `eat_entry` for each entry.
 ///
-/// If the given TokenStream is not valid, errors are emitted as appropriate.
-/// Erroneous types will not be put into the Vec but rather simply skipped,
-/// the emitted errors will abort the compilation anyway.
-pub(crate) fn parse_types(args: TokenStream) -> Vec<ProxyType> {
+/// Parsing does not stop at the first invalid entry: the error is recorded,
+/// all tokens up to the next comma are skipped and parsing continues from
+/// there. That way, all problems are reported at once. The returned error is
+/// the combination of all errors that occurred.
+fn parse_list<T>(
+    args: TokenStream,
+    mut eat_entry: impl FnMut(&mut TokenIter) -> syn::Result<T>,
+) -> (Vec<T>, Option<Error>) {
     let mut out = Vec::new();
+    let mut error = None;
     let mut iter = args.into_iter().peekable();
 
     // While there are still tokens left...
     while iter.peek().is_some() {
-        // First, we expect one of the proxy types.
-        if let Ok(ty) = eat_type(&mut iter) {
-            out.push(ty);
+        match eat_entry(&mut iter) {
+            Ok(entry) => out.push(entry),
+            Err(err) => {
+                combine_error(&mut error, err);
+                skip_to_comma(&mut iter);
+            }
+        }
+
+        // After each entry, we expect either a comma or the end of the list.
+        match iter.next() {
+            None => break,
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => {}
+            Some(other) => {
+                let err = Error::new(
+                    other.span().into(),
+                    format_args!("expected `,` between proxy types, found `{}`", other),
+                );
+                combine_error(&mut error, err);
+                skip_to_comma(&mut iter);
+                let _ = iter.next();
+            }
+        }
+    }
+
+    (out, error)
+}
+
+/// Skips all tokens up to (but not including) the next comma.
+fn skip_to_comma(iter: &mut TokenIter) {
+    while let Some(tt) = iter.peek() {
+        if matches!(tt, TokenTree::Punct(punct) if punct.as_char() == ',') {
+            break;
+        }
+        let _ = iter.next();
+    }
+}
+
+/// Adds an error for every proxy type that was already listed before.
+fn check_duplicates(types: impl Iterator<Item = (ProxyType, Span)>, error: &mut Option<Error>) {
+    let mut seen = Vec::new();
+    for (ty, span) in types {
+        if seen.contains(&ty) {
+            let err = Error::new(
+                span.into(),
+                format_args!("duplicate proxy type `{}`", ty.name()),
+            );
+            combine_error(error, err);
+        } else {
+            seen.push(ty);
+        }
+    }
+}
+
+fn combine_error(error: &mut Option<Error>, err: Error) {
+    match error {
+        Some(current_err) => current_err.combine(err),
+        None => *error = Some(err),
+    }
+}
+
+/// Parses `where <predicate>` after the given proxy types from the given
+/// token iterator, if there is one. The predicate ends at the next comma.
+fn eat_extra_bounds(iter: &mut TokenIter, types: &[(ProxyType, Span)]) -> syn::Result<ExtraBounds> {
+    let where_span = match iter.peek() {
+        Some(TokenTree::Ident(id)) if id.to_string() == "where" => id.span(),
+        _ => return Ok(ExtraBounds::default()),
+    };
+
+    // Proxy types without a type parameter have nothing the placeholder could
+    // refer to.
+    if let Some((ty, _)) = types.iter().find(|(ty, _)| {
+        matches!(
+            ty,
+            ProxyType::Tuple(_) | ProxyType::Enum(_) | ProxyType::Delegate(_)
+        )
+    }) {
+        return Err(Error::new(
+            where_span.into(),
+            format_args!("`where` bounds are not supported for `{}`", ty.name()),
+        ));
+    }
+
+    let parser = |input: ParseStream| {
+        let where_token = input.parse::<Token![where]>()?;
+        if input.is_empty() || input.peek(Token![,]) {
+            return Err(Error::new(
+                where_token.span,
+                "expected a bound after `where`, e.g. `Arc where T: Send + Sync`",
+            ));
         }
+        let predicate = input.parse::<WherePredicate>()?;
+        if !input.is_empty() && !input.peek(Token![,]) {
+            return Err(input.error(
+                "expected `,` after the bound; list a single predicate after `where`, e.g. \
+                `Arc where T: Send + Sync`",
+            ));
+        }
+        Ok((predicate, input.parse::<TokenStream2>()?))
+    };
+    // On errors, the iterator is restored so that the tokens up to the next
+    // comma can be skipped.
+    let tokens = iter.collect::<TokenStream>();
+    *iter = tokens.clone().into_iter().peekable();
+    let (predicate, rest) = parser.parse(tokens)?;
+    *iter = TokenStream::from(rest).into_iter().peekable();
+
+    Ok(ExtraBounds(vec![predicate]))
+}
 
-        // If the next token is a comma, we eat it (trailing commas are
-        // allowed). If not, nothing happens (in this case, it's probably the
-        // end of the stream, otherwise an error will occur later).
-        let comma_next =
-            matches!(iter.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == ',');
+/// Parses `unsafe(<types>)` from the given token iterator. The next token
+/// must be the `unsafe` keyword!
+fn eat_unsafe_group(iter: &mut TokenIter) -> syn::Result<Vec<(ProxyType, Span)>> {
+    // Eat `unsafe`
+    let unsafe_token = iter.next().unwrap();
 
-        if comma_next {
+    match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            let stream = group.stream();
             let _ = iter.next();
+            match parse_list(stream, eat_types) {
+                (types, None) => Ok(types.into_iter().flatten().collect()),
+                (_, Some(err)) => Err(err),
+            }
         }
+        _ => Err(Error::new(
+            unsafe_token.span().into(),
+            "expected a parenthesized list of types after `unsafe`, \
+                e.g. `#[auto_impl(unsafe(&, Box))]`",
+        )),
     }
+}
 
-    out
+/// Parses one entry of a list of proxy types from the given token iterator.
+/// This is a single `ProxyType` (see `eat_type`) or a range of tuple types
+/// declared as `tuples(<min>..=<max>)`. The iterator must not be empty!
+fn eat_types(iter: &mut TokenIter) -> syn::Result<Vec<(ProxyType, Span)>> {
+    match iter.peek() {
+        Some(TokenTree::Ident(id)) if id.to_string() == "tuples" => {
+            let span = iter.next().unwrap().span();
+            let arities = eat_tuple_arities(iter, span)?;
+            Ok(arities
+                .map(|arity| (ProxyType::Tuple(arity), span))
+                .collect())
+        }
+        _ => Ok(vec![eat_type(iter)?]),
+    }
 }
 
-/// Parses one `ProxyType` from the given token iterator. The iterator must not
-/// be empty!
-fn eat_type(iter: &mut Peekable<token_stream::IntoIter>) -> syn::Result<ProxyType> {
+/// Parses the parenthesized part of `tuples(<min>..=<max>)` from the given
+/// token iterator. `span` is the span of the `tuples` keyword. A single number
+/// (`tuples(<n>)`) and exclusive ranges are accepted as well.
+fn eat_tuple_arities(iter: &mut TokenIter, span: Span) -> syn::Result<RangeInclusive<usize>> {
+    const FORMAT_TEXT: &str = "tuple proxy types are declared as `tuples(<min>..=<max>)` \
+        where `<min>` and `<max>` are the smallest and largest number of elements, e.g. \
+        `tuples(1..=12)`";
+
+    let stream = match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        _ => return Err(Error::new(span.into(), FORMAT_TEXT)),
+    };
+    let _ = iter.next();
+
+    let parser = |input: ParseStream| {
+        let min = input.parse::<LitInt>()?;
+        let max = if input.is_empty() {
+            min.base10_parse()?
+        } else if input.peek(Token![..=]) {
+            input.parse::<Token![..=]>()?;
+            input.parse::<LitInt>()?.base10_parse()?
+        } else {
+            input.parse::<Token![..]>()?;
+            let end = input.parse::<LitInt>()?;
+            match end.base10_parse::<usize>()?.checked_sub(1) {
+                Some(max) => max,
+                None => return Err(Error::new(end.span(), "empty range of tuple sizes")),
+            }
+        };
+        let min_span = min.span();
+        let min = min.base10_parse::<usize>()?;
+
+        if min == 0 {
+            return Err(Error::new(
+                min_span,
+                "tuple proxy types need at least one element",
+            ));
+        }
+        if min > max {
+            return Err(input.error(format_args!("empty range of tuple sizes; {}", FORMAT_TEXT)));
+        }
+        Ok(min..=max)
+    };
+
+    parser.parse2(stream.into())
+}
+
+/// Parses one `ProxyType` from the given token iterator and returns it
+/// together with the span of its first token. The iterator must not be empty!
+fn eat_type(iter: &mut TokenIter) -> syn::Result<(ProxyType, Span)> {
     #[rustfmt::skip]
     const NOTE_TEXT: &str = "\
         attribute format should be `#[auto_impl(<types>)]` where `<types>` is \
         a comma-separated list of types. Allowed values for types: `&`, \
-        `&mut`, `Box`, `Rc`, `Arc`, `Fn`, `FnMut` and `FnOnce`.\
+        `&mut`, `Box`, `Rc`, `Arc`, `Cow`, `Pin`, `ManuallyDrop`, \
+        `AssertUnwindSafe`, `Wrapping`, `Reverse`, `Option`, `Vec`, `slice`, \
+        `array`, `Fn`, `FnMut`, `FnOnce`, `tuples(<min>..=<max>)`, \
+        `enum <name>(<types>)`, `delegate(<type> => self.<field>)` and \
+        `custom(<type>, <capabilities>)`.\
     ";
-    const EXPECTED_TEXT: &str = "expected '&' or ident.";
+    const EXPECTED_TEXT: &str = "expected `&`, the name of a proxy type, `unsafe(...)`, \
+        `tuples(...)`, `enum <name>(...)`, `delegate(...)` or `custom(...)`.";
 
     // We can unwrap because this function requires the iterator to be
     // non-empty.
-    let ty = match iter.next().unwrap() {
+    let first = iter.next().unwrap();
+    let span = first.span();
+    let ty = match first {
         TokenTree::Group(group) => {
             return Err(Error::new(
                 group.span().into(),
@@ -113,19 +834,273 @@ fn eat_type(iter: &mut Peekable<token_stream::IntoIter>) -> syn::Result<ProxyTyp
             "Box" => ProxyType::Box,
             "Rc" => ProxyType::Rc,
             "Arc" => ProxyType::Arc,
+            "Cow" => ProxyType::Cow,
+            "Pin" => ProxyType::Pin,
+            "ManuallyDrop" => ProxyType::ManuallyDrop,
+            "AssertUnwindSafe" => ProxyType::AssertUnwindSafe,
+            "Wrapping" => ProxyType::Wrapping,
+            "Reverse" => ProxyType::Reverse,
+            "Option" => ProxyType::Option,
+            "Vec" => ProxyType::Vec,
+            "slice" => ProxyType::Slice,
+            "array" => ProxyType::Array,
             "Fn" => ProxyType::Fn,
             "FnMut" => ProxyType::FnMut,
             "FnOnce" => ProxyType::FnOnce,
-            _ => {
+            "custom" => ProxyType::Custom(Box::new(eat_custom_proxy(iter, span)?)),
+            "enum" => ProxyType::Enum(Box::new(eat_enum_proxy(iter, span)?)),
+            "delegate" => ProxyType::Delegate(Box::new(eat_delegate_proxy(iter, span)?)),
+            name => {
+                let msg = match suggest_proxy_name(name) {
+                    Some(suggestion) => format!(
+                        "unknown proxy type `{}`, did you mean `{}`?\n{}",
+                        name, suggestion, NOTE_TEXT,
+                    ),
+                    None => format!("unknown proxy type `{}`\n{}", name, NOTE_TEXT),
+                };
+                return Err(Error::new(ident.span().into(), msg));
+            }
+        },
+    };
+
+    Ok((ty, span))
+}
+
+/// Parses the parenthesized part of `custom(<type>, <capabilities>)` from the
+/// given token iterator. `span` is the span of the `custom` keyword.
+fn eat_custom_proxy(iter: &mut TokenIter, span: Span) -> syn::Result<CustomProxy> {
+    const FORMAT_TEXT: &str =
+        "custom proxy types are declared as `custom(<type>, <capabilities>)` \
+        where `<type>` contains the placeholder `T` for the proxied type and `<capabilities>` is \
+        a comma-separated list of `deref`, `deref_mut` and `into_inner`, e.g. \
+        `custom(MyPtr<T>, deref)`";
+
+    let stream = match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        _ => return Err(Error::new(span.into(), FORMAT_TEXT)),
+    };
+    let _ = iter.next();
+
+    let parser = |input: ParseStream| {
+        let mut custom = CustomProxy {
+            ty: input.parse()?,
+            deref: false,
+            deref_mut: false,
+            into_inner: false,
+        };
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let capability = input.parse::<Ident>()?;
+            let enabled = match &*capability.to_string() {
+                "deref" => &mut custom.deref,
+                "deref_mut" => &mut custom.deref_mut,
+                "into_inner" => &mut custom.into_inner,
+                _ => {
+                    return Err(Error::new(
+                        capability.span(),
+                        format_args!("unknown capability `{}`; {}", capability, FORMAT_TEXT),
+                    ))
+                }
+            };
+            if *enabled {
                 return Err(Error::new(
-                    ident.span().into(),
-                    format_args!("unexpected '{}', {}\n{}", ident, EXPECTED_TEXT, NOTE_TEXT),
+                    capability.span(),
+                    format_args!("duplicate capability `{}`", capability),
                 ));
             }
-        },
+            *enabled = true;
+        }
+
+        // `DerefMut` requires `Deref`.
+        custom.deref |= custom.deref_mut;
+
+        if !custom.mentions_placeholder() {
+            return Err(Error::new_spanned(
+                &custom.ty,
+                format_args!(
+                    "the type of a custom proxy has to use the placeholder `{}` for the \
+                        proxied type, e.g. `custom(MyPtr<{}>, deref)`",
+                    CustomProxy::PLACEHOLDER,
+                    CustomProxy::PLACEHOLDER,
+                ),
+            ));
+        }
+
+        Ok(custom)
+    };
+
+    parser.parse2(stream.into())
+}
+
+/// Parses the part of `enum Name(<types>)` after the `enum` keyword from the
+/// given token iterator. `span` is the span of the `enum` keyword. The list of
+/// types can be omitted (which is only useful in `keep_default_for`).
+fn eat_enum_proxy(iter: &mut TokenIter, span: Span) -> syn::Result<EnumProxy> {
+    const FORMAT_TEXT: &str = "enums are declared as `enum <name>(<types>)` where `<types>` is \
+        a comma-separated list of the types of the variants, e.g. \
+        `enum AnyStore(MemStore, DiskStore)`";
+
+    let ident = match iter.next() {
+        Some(TokenTree::Ident(ident)) => Ident::new(&ident.to_string(), ident.span().into()),
+        _ => return Err(Error::new(span.into(), FORMAT_TEXT)),
     };
 
-    Ok(ty)
+    let stream = match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        _ => {
+            return Ok(EnumProxy {
+                ident,
+                variants: Vec::new(),
+                user_defined: false,
+            })
+        }
+    };
+    let _ = iter.next();
+
+    let parser = |input: ParseStream| {
+        let types = Punctuated::<Type, Token![,]>::parse_terminated(input)?;
+
+        let mut variants: Vec<(Ident, Type)> = Vec::new();
+        for ty in types {
+            // The variant is named after the type.
+            let name = match &ty {
+                Type::Path(p) if p.qself.is_none() => p.path.segments.last().unwrap().ident.clone(),
+                _ => {
+                    return Err(Error::new_spanned(
+                        &ty,
+                        format_args!(
+                            "the types of the variants of an enum have to be paths, as the \
+                                variants are named after them; {}",
+                            FORMAT_TEXT,
+                        ),
+                    ));
+                }
+            };
+            if variants.iter().any(|(other, _)| *other == name) {
+                return Err(Error::new_spanned(
+                    &ty,
+                    format_args!("duplicate variant `{}` in enum `{}`", name, ident),
+                ));
+            }
+            variants.push((name, ty));
+        }
+
+        Ok(variants)
+    };
+
+    Ok(EnumProxy {
+        variants: parser.parse2(stream.into())?,
+        ident,
+        user_defined: false,
+    })
+}
+
+/// Parses the parenthesized part of `delegate(<type> => self.<field>)` from
+/// the given token iterator. `span` is the span of the `delegate` keyword. The
+/// field can be omitted (which is only useful in `keep_default_for`).
+fn eat_delegate_proxy(iter: &mut TokenIter, span: Span) -> syn::Result<DelegateProxy> {
+    const FORMAT_TEXT: &str = "delegating structs are declared as \
+        `delegate(<type> => self.<field>)` where `<field>` is the name or index of the field \
+        the methods are forwarded to, optionally followed by its type, e.g. \
+        `delegate(Instrumented => self.inner)` or `delegate(Wrapper => self.0: Vec<u8>)`";
+
+    let stream = match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        _ => return Err(Error::new(span.into(), FORMAT_TEXT)),
+    };
+    let _ = iter.next();
+
+    let parser = |input: ParseStream| {
+        let mut delegate = DelegateProxy {
+            ty: input.parse()?,
+            field: None,
+            field_ty: None,
+            generics: Generics::default(),
+        };
+        if input.is_empty() {
+            return Ok(delegate);
+        }
+
+        input
+            .parse::<Token![=>]>()
+            .and_then(|_| input.parse::<Token![self]>())
+            .and_then(|_| input.parse::<Token![.]>())
+            .map_err(|e| Error::new(e.span(), FORMAT_TEXT))?;
+        delegate.field = Some(input.parse()?);
+
+        if input.peek(Token![:]) {
+            input.parse::<Token![:]>()?;
+            delegate.field_ty = Some(input.parse()?);
+        }
+
+        Ok(delegate)
+    };
+
+    parser.parse2(stream.into())
+}
+
+/// Returns the name of the proxy type that is most similar to the given
+/// unknown name, if there is one that is similar enough to likely be meant.
+fn suggest_proxy_name(name: &str) -> Option<String> {
+    if name == "mut" {
+        return Some("&mut".to_owned());
+    }
+
+    let name = name.to_lowercase();
+    ProxyType::ALL
+        .iter()
+        .map(|ty| ty.name())
+        .map(|candidate| {
+            let distance = edit_distance(&name, &candidate.to_lowercase());
+            (candidate, distance)
+        })
+        .filter(|(candidate, distance)| *distance <= 1 || distance * 3 <= candidate.len())
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// Returns the number of single character insertions, deletions,
+/// substitutions and transpositions of adjacent characters needed to turn
+/// `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // `d[i][j]` is the distance between the first `i` chars of `a` and the
+    // first `j` chars of `b`.
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
 }
 
 // Right now, we can't really write useful tests. Many functions from
@@ -1,9 +1,9 @@
 use auto_impl::auto_impl;
 
 
-trait Foo {}
+struct Foo;
 
 #[auto_impl(&, &mut)]
-impl Foo for usize {}
+impl Foo {}
 
 fn main() {}
@@ -0,0 +1,22 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, enum Either(Left, Right))]
+mod traits {
+    #[auto_impl(&)]
+    pub trait Reset {
+        fn reset(&mut self);
+    }
+
+    #[auto_impl(&)]
+    #[cfg_attr(all(), auto_impl(Box, &))]
+    pub trait Get {
+        fn get(&self) -> u32;
+    }
+}
+
+struct Left;
+struct Right;
+
+
+fn main() {}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Wrapping)]
+trait Foo {
+    fn foo(self: Box<Self>);
+}
+
+
+fn main() {}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&mut)]
+trait Builder {
+    fn set_timeout(&self, secs: u32) -> &mut Self;
+}
+
+
+fn main() {}
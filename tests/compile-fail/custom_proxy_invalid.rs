@@ -0,0 +1,12 @@
+use auto_impl::auto_impl;
+
+
+struct MyPtr<T>(T);
+
+#[auto_impl(custom(MyPtr<u8>, deref), custom(MyPtr<T>, deref, derf), custom)]
+trait Foo {
+    fn foo(&self);
+}
+
+
+fn main() {}
@@ -0,0 +1,14 @@
+use auto_impl::auto_impl;
+
+
+struct MyPtr<T>(T);
+
+#[auto_impl(custom(MyPtr<T>, deref))]
+trait Foo {
+    fn foo(&self);
+    fn bar(&mut self);
+    fn baz(self);
+}
+
+
+fn main() {}
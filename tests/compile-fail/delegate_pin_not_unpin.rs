@@ -0,0 +1,32 @@
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(
+    delegate(Inferred => self.inner),
+    delegate(Typed => self.inner: Pinned),
+)]
+trait Poll {
+    fn poll(self: Pin<&mut Self>) -> u32;
+}
+
+struct Pinned(PhantomPinned);
+
+impl Poll for Pinned {
+    fn poll(self: Pin<&mut Self>) -> u32 {
+        0
+    }
+}
+
+struct Inferred {
+    inner: Pinned,
+}
+
+struct Typed {
+    inner: Pinned,
+}
+
+
+fn main() {}
@@ -0,0 +1,18 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(delegate(Wrapper => self.inner))]
+trait Foo {
+    type Out;
+
+    fn create() -> u32;
+    fn get(&self) -> u32;
+    fn boxed(self: Box<Self>);
+}
+
+struct Wrapper {
+    inner: u32,
+}
+
+
+fn main() {}
@@ -0,0 +1,29 @@
+use auto_impl::{auto_impl, Delegate};
+
+
+#[auto_impl(export_descriptor)]
+pub trait Foo {
+    fn foo(&self);
+}
+
+#[derive(Delegate)]
+struct NoAttribute(u32);
+
+#[derive(Delegate)]
+#[delegate(Foo)]
+struct TwoFields(u32, u32);
+
+#[derive(Delegate)]
+#[delegate(Foo, to = missing)]
+struct MissingField {
+    inner: u32,
+}
+
+#[derive(Delegate)]
+#[delegate(Foo)]
+enum NamedFields {
+    A { inner: u32 },
+}
+
+
+fn main() {}
@@ -0,0 +1,12 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Box)]
+#[cfg_attr(all(), auto_impl(Box, Arc))]
+#[auto_impl(Rc, &)]
+trait Foo {
+    fn foo(&self);
+}
+
+
+fn main() {}
@@ -0,0 +1,27 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(enum Either(Left, Right))]
+trait Foo {
+    type Out;
+    fn get(&self) -> Self::Out;
+}
+
+struct Left;
+impl Foo for Left {
+    type Out = u32;
+    fn get(&self) -> u32 {
+        0
+    }
+}
+
+struct Right;
+impl Foo for Right {
+    type Out = String;
+    fn get(&self) -> String {
+        String::new()
+    }
+}
+
+
+fn main() {}
@@ -0,0 +1,16 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(enum Either(Left, Right))]
+trait Foo {
+    const ID: u32;
+
+    fn new() -> Self;
+    fn get(&self) -> u32;
+}
+
+struct Left;
+struct Right;
+
+
+fn main() {}
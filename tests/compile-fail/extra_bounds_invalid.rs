@@ -0,0 +1,24 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Box where T: Send Sync, tuples(1..=2) where T: Send, Arc where)]
+trait Foo {
+    fn foo(&self);
+}
+
+#[auto_impl(&, Box where T: Send)]
+trait Convert<T> {
+    fn convert(&self) -> T;
+}
+
+struct Bar;
+
+#[auto_impl(&, Box where T: Send)]
+impl PartialEq<u32> for Bar {
+    fn eq(&self, _: &u32) -> bool {
+        true
+    }
+}
+
+
+fn main() {}
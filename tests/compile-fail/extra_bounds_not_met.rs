@@ -0,0 +1,26 @@
+use std::{cell::Cell, sync::Arc};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Arc where T: Send + Sync)]
+trait Worker {
+    fn work(&self) -> u32;
+}
+
+struct Local(Cell<u32>);
+
+impl Worker for Local {
+    fn work(&self) -> u32 {
+        self.0.get()
+    }
+}
+
+fn work<W: Worker>(w: W) -> u32 {
+    w.work()
+}
+
+
+fn main() {
+    work(Arc::new(Local(Cell::new(1))));
+}
@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Vec)]
+trait Foo {
+    fn notify(&self);
+    fn count(&self) -> usize;
+}
+
+
+fn main() {}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Vec, tuples(2))]
+trait Sink {
+    fn send(&self, id: u32, message: String);
+}
+
+
+fn main() {}
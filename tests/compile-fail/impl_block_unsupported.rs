@@ -0,0 +1,21 @@
+use auto_impl::auto_impl;
+
+
+trait Counter {
+    fn get(&self) -> u32;
+    fn increment(&mut self);
+}
+
+struct Simple(u32);
+
+#[auto_impl(&, Vec)]
+impl Counter for Simple {
+    fn get(&self) -> u32 {
+        self.0
+    }
+    fn increment(&mut self) {
+        self.0 += 1;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Bxo, 3, Arc Rc, fnmut, Foo)]
+trait Foo {
+    fn foo(&self);
+}
+
+
+fn main() {}
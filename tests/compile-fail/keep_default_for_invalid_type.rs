@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Rc)]
+trait Foo {
+    #[auto_impl(keep_default_for(&, Rcc))]
+    fn foo(&self) {}
+}
+
+
+fn main() {}
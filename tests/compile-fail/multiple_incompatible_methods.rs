@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Rc, Arc)]
+trait Foo {
+    fn foo(&mut self);
+    fn bar(self);
+}
+
+
+fn main() {}
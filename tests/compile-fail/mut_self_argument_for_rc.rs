@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Rc)]
+trait Foo {
+    fn absorb(&self, other: &mut Self);
+}
+
+
+fn main() {}
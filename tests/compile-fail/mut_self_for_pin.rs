@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Pin)]
+trait Foo {
+    fn foo(&mut self);
+}
+
+
+fn main() {}
@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Option)]
+trait Foo {
+    fn name(&self) -> &str;
+    fn parse(&self) -> Result<u32, String>;
+}
+
+
+fn main() {}
@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Box)]
+trait Size {
+    fn larger<'a>(&'a self, other: &'a Self) -> &'a Self;
+    fn fallback(&self) -> &'static Self;
+}
+
+
+fn main() {}
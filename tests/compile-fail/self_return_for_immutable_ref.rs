@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&)]
+trait Foo {
+    fn new(cfg: u32) -> Self;
+}
+
+
+fn main() {}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&)]
+trait Foo {
+    fn merge_all(&self, others: &[Self]) where Self: Sized;
+}
+
+
+fn main() {}
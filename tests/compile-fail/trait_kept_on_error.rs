@@ -0,0 +1,23 @@
+use auto_impl::auto_impl;
+
+
+// The impl for `&` cannot be generated, but the trait and the impl for `Box`
+// are still emitted, so that using them does not cause additional errors.
+#[auto_impl(&, Box)]
+trait Foo {
+    fn foo(&mut self);
+}
+
+struct Bar;
+
+impl Foo for Bar {
+    fn foo(&mut self) {}
+}
+
+fn assert_foo<T: Foo>() {}
+
+
+fn main() {
+    assert_foo::<Bar>();
+    assert_foo::<Box<Bar>>();
+}
@@ -0,0 +1,14 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(tuples(2..=3))]
+trait Foo {
+    const ID: u32;
+    type Out;
+
+    fn new() -> u32;
+    fn notify(&self);
+}
+
+
+fn main() {}
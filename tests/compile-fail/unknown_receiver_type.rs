@@ -0,0 +1,12 @@
+use std::pin::Pin;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Box)]
+trait Foo {
+    fn foo(self: Pin<Box<Self>>);
+}
+
+
+fn main() {}
@@ -0,0 +1,10 @@
+use auto_impl::auto_impl;
+
+
+#[auto_impl(unsafe(Box))]
+trait Foo {
+    fn foo(&self);
+}
+
+
+fn main() {}
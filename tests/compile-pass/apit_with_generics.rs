@@ -0,0 +1,19 @@
+use std::fmt::Debug;
+
+use auto_impl::auto_impl;
+
+
+trait Visitor {
+    fn visit(&mut self, x: u32);
+}
+
+#[auto_impl(&, &mut, Box)]
+trait Visitable {
+    fn visit<V: Visitor>(&self, v: V, extra: impl Debug);
+    fn visit_all<'a, V: Visitor + 'a>(&self, vs: Vec<&'a mut V>, extra: impl Debug);
+    fn only_apit(&self, extra: impl Debug, more: impl Iterator<Item = u32>);
+    fn with_const<const N: usize>(&self, data: [u8; N], extra: impl Debug);
+}
+
+
+fn main() {}
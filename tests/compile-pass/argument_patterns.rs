@@ -0,0 +1,44 @@
+use auto_impl::auto_impl;
+
+
+struct Ctx;
+
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[auto_impl(&, &mut, Box)]
+trait Handler {
+    fn handle(&self, _: Ctx, arg1: u32) -> u32;
+
+    fn provided(&self, (a, b): (u32, u32), Point { x, .. }: Point, _: Ctx) -> u32 {
+        a + b + x
+    }
+}
+
+#[auto_impl(Fn)]
+trait Callback {
+    fn call(&self, _: Ctx, _: u32) -> u32;
+}
+
+
+struct Double;
+
+impl Handler for Double {
+    fn handle(&self, _: Ctx, x: u32) -> u32 {
+        2 * x
+    }
+
+    fn provided(&self, (a, b): (u32, u32), p: Point, _: Ctx) -> u32 {
+        2 * (a + b + p.x + p.y)
+    }
+}
+
+fn main() {
+    assert_eq!((&Double).handle(Ctx, 3), 6);
+    assert_eq!(Box::new(Double).provided((1, 2), Point { x: 3, y: 4 }, Ctx), 20);
+
+    let f = |_: Ctx, x: u32| x + 1;
+    assert_eq!(Callback::call(&f, Ctx, 1), 2);
+}
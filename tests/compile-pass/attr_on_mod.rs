@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Box, Arc)]
+mod traits {
+    pub trait Greet {
+        fn greet(&self) -> String;
+    }
+
+    pub trait Counter {
+        fn count(&self) -> u32;
+
+        #[auto_impl(keep_default_for(&))]
+        fn double(&self) -> u32 {
+            self.count() * 2
+        }
+    }
+
+    #[auto_impl(&mut, Box)]
+    pub trait Reset {
+        fn reset(&mut self);
+    }
+
+    #[auto_impl(skip)]
+    pub trait Named {
+        fn name(&self) -> &'static str;
+    }
+
+    pub struct NotATrait;
+}
+
+#[allow(deprecated)]
+#[auto_impl(&, Rc)]
+mod other {
+    pub trait Take {
+        fn take(self) -> u32;
+    }
+}
+
+use other::Take;
+use traits::{Counter, Greet, Named, Reset};
+
+struct Thing(u32);
+
+impl Greet for Thing {
+    fn greet(&self) -> String {
+        format!("hello {}", self.0)
+    }
+}
+
+impl Counter for Thing {
+    fn count(&self) -> u32 {
+        self.0
+    }
+
+    fn double(&self) -> u32 {
+        self.0 + self.0
+    }
+}
+
+impl Reset for Thing {
+    fn reset(&mut self) {
+        self.0 = 0;
+    }
+}
+
+impl Named for Thing {
+    fn name(&self) -> &'static str {
+        "thing"
+    }
+}
+
+impl Take for Thing {
+    fn take(self) -> u32 {
+        self.0
+    }
+}
+
+fn greet_twice<G: Greet>(g: G) -> String {
+    format!("{} {}", g.greet(), g.greet())
+}
+
+fn reset<R: Reset>(mut r: R) {
+    r.reset();
+}
+
+fn main() {
+    let mut thing = Thing(3);
+    assert_eq!(greet_twice(&thing), "hello 3 hello 3");
+    assert_eq!(greet_twice(Box::new(Thing(1))), "hello 1 hello 1");
+    assert_eq!(Arc::new(Thing(4)).count(), 4);
+    assert_eq!(Counter::double(&&thing), 6);
+
+    reset(&mut thing);
+    assert_eq!(thing.name(), "thing");
+    assert_eq!(Thing(5).take(), 5);
+    let _ = traits::NotATrait;
+}
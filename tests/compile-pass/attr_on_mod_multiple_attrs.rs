@@ -0,0 +1,72 @@
+use std::rc::Rc;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&)]
+mod traits {
+    #[auto_impl(&)]
+    #[auto_impl(Box)]
+    pub trait Get {
+        fn get(&self) -> u32;
+
+        #[auto_impl(keep_default_for(Box))]
+        fn name(&self) -> &'static str {
+            "default"
+        }
+    }
+
+    // Like `#[auto_impl]`, an enabled `cfg_attr` replaces the proxy types of
+    // the module.
+    #[cfg_attr(all(), auto_impl(&, Box))]
+    #[cfg_attr(any(), auto_impl(Rc))]
+    pub trait Count {
+        fn count(&self) -> u32;
+    }
+
+    #[cfg_attr(any(), auto_impl(&mut))]
+    #[cfg_attr(all(), cfg_attr(all(), auto_impl(Rc)))]
+    pub trait Reset {
+        fn reset(&self);
+    }
+}
+
+use traits::{Count, Get, Reset};
+
+struct Thing;
+
+impl Get for Thing {
+    fn get(&self) -> u32 {
+        1
+    }
+
+    fn name(&self) -> &'static str {
+        "thing"
+    }
+}
+
+impl Count for Thing {
+    fn count(&self) -> u32 {
+        2
+    }
+}
+
+impl Reset for Thing {
+    fn reset(&self) {}
+}
+
+fn get<G: Get>(g: G) -> u32 {
+    g.get()
+}
+
+fn count<C: Count>(c: C) -> u32 {
+    c.count()
+}
+
+fn main() {
+    assert_eq!(get(&Thing) + get(Box::new(Thing)), 2);
+    assert_eq!(Get::name(&&Thing), "thing");
+    assert_eq!(Box::new(Thing).name(), "default");
+    assert_eq!(count(&Thing) + count(Box::new(Thing)), 4);
+    Rc::new(Thing).reset();
+}
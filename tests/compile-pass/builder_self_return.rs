@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&mut, Box)]
+trait Builder {
+    fn set_timeout(&mut self, d: Duration) -> &mut Self;
+    fn inspect(&self) -> &Self;
+    fn inspect_mut(&mut self) -> &Self;
+    fn with_label<'a>(&'a self, label: &str) -> &'a Self;
+    fn timeout(&self) -> Duration;
+}
+
+#[auto_impl(&, Rc, Arc, Pin)]
+trait Chain {
+    fn log(&self, msg: &str) -> &Self;
+}
+
+
+#[derive(Default)]
+struct Client {
+    timeout: Duration,
+}
+
+impl Builder for Client {
+    fn set_timeout(&mut self, d: Duration) -> &mut Self {
+        self.timeout = d;
+        self
+    }
+    fn inspect(&self) -> &Self {
+        self
+    }
+    fn inspect_mut(&mut self) -> &Self {
+        self
+    }
+    fn with_label<'a>(&'a self, _label: &str) -> &'a Self {
+        self
+    }
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+fn configure<B: Builder>(mut b: B) -> Duration {
+    b.set_timeout(Duration::from_secs(1))
+        .set_timeout(Duration::from_secs(2))
+        .inspect()
+        .with_label("client")
+        .timeout()
+}
+
+fn main() {
+    let mut client = Client::default();
+    assert_eq!(configure(&mut client), Duration::from_secs(2));
+    assert_eq!(client.timeout(), Duration::from_secs(2));
+    assert_eq!(configure(Box::new(Client::default())), Duration::from_secs(2));
+
+    // The returned reference is the proxy itself.
+    let boxed = Box::new(Client { timeout: Duration::from_secs(5) });
+    assert!(std::ptr::eq(boxed.inspect(), &boxed));
+    assert_eq!(boxed.with_label("boxed").timeout(), Duration::from_secs(5));
+}
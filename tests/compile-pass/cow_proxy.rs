@@ -0,0 +1,66 @@
+use std::{borrow::Cow, path::Path};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Cow)]
+trait Describe {
+    fn describe(&self) -> String;
+    fn same_as(&self, other: &Self) -> bool;
+    fn default_name() -> &'static str;
+}
+
+impl Describe for str {
+    fn describe(&self) -> String {
+        format!("string {:?}", self)
+    }
+    fn same_as(&self, other: &Self) -> bool {
+        self == other
+    }
+    fn default_name() -> &'static str {
+        "str"
+    }
+}
+
+impl Describe for Path {
+    fn describe(&self) -> String {
+        format!("path {}", self.display())
+    }
+    fn same_as(&self, other: &Self) -> bool {
+        self == other
+    }
+    fn default_name() -> &'static str {
+        "path"
+    }
+}
+
+impl<T: std::fmt::Debug + Clone + PartialEq> Describe for [T] {
+    fn describe(&self) -> String {
+        format!("slice {:?}", self)
+    }
+    fn same_as(&self, other: &Self) -> bool {
+        self == other
+    }
+    fn default_name() -> &'static str {
+        "slice"
+    }
+}
+
+fn describe(x: impl Describe) -> String {
+    x.describe()
+}
+
+
+fn main() {
+    let borrowed: Cow<'_, str> = Cow::Borrowed("hi");
+    let owned: Cow<'_, str> = Cow::Owned("hi".to_string());
+    assert_eq!(describe(borrowed.clone()), "string \"hi\"");
+    assert!(borrowed.same_as(&owned));
+    assert_eq!(<Cow<'_, str> as Describe>::default_name(), "str");
+
+    let path: Cow<'_, Path> = Cow::Borrowed(Path::new("/tmp"));
+    assert_eq!(describe(path), "path /tmp");
+
+    let slice: Cow<'_, [u8]> = Cow::Owned(vec![1, 2]);
+    assert_eq!(describe(slice), "slice [1, 2]");
+}
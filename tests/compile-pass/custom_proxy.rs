@@ -0,0 +1,132 @@
+use std::{
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+use auto_impl::auto_impl;
+
+
+/// An owning pointer that supports all receivers.
+struct MyBox<T>(T);
+
+impl<T> MyBox<T> {
+    fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for MyBox<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for MyBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// A pointer with a lifetime parameter, like an arena box.
+struct ArenaBox<'a, T>(&'a mut T);
+
+impl<T> Deref for ArenaBox<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T> DerefMut for ArenaBox<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0
+    }
+}
+
+mod shared {
+    /// A shared pointer defined in another module.
+    pub struct Shared<T>(pub std::rc::Rc<T>);
+
+    impl<T> std::ops::Deref for Shared<T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            &self.0
+        }
+    }
+}
+
+
+#[auto_impl(custom(MyBox<T>, deref_mut, into_inner), custom(ArenaBox<'a, T>, deref_mut))]
+trait Counter {
+    fn get(&self) -> u32;
+    fn increment(&mut self);
+    fn same_as(&self, other: &Self) -> bool;
+    fn name() -> &'static str;
+}
+
+#[auto_impl(&, custom(MyBox<T>, deref, into_inner), custom(shared::Shared<T>, deref))]
+trait Consume {
+    fn peek(&self) -> u32;
+
+    #[auto_impl(keep_default_for(&, custom(shared::Shared<T>)))]
+    fn consume(self) -> u32
+    where
+        Self: Sized,
+    {
+        self.peek()
+    }
+}
+
+struct Simple(u32);
+
+impl Counter for Simple {
+    fn get(&self) -> u32 {
+        self.0
+    }
+    fn increment(&mut self) {
+        self.0 += 1;
+    }
+    fn same_as(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+    fn name() -> &'static str {
+        "simple"
+    }
+}
+
+impl Consume for Simple {
+    fn peek(&self) -> u32 {
+        self.0
+    }
+    fn consume(self) -> u32 {
+        self.0 * 10
+    }
+}
+
+fn assert_counter<T: Counter>() {}
+fn assert_consume<T: Consume>() {}
+
+
+fn main() {
+    assert_counter::<MyBox<Simple>>();
+    assert_counter::<ArenaBox<'static, Simple>>();
+    assert_consume::<&Simple>();
+    assert_consume::<MyBox<Simple>>();
+    assert_consume::<shared::Shared<Simple>>();
+
+    let mut b = MyBox(Simple(1));
+    b.increment();
+    assert_eq!(b.get(), 2);
+    assert!(b.same_as(&MyBox(Simple(2))));
+    assert_eq!(<MyBox<Simple> as Counter>::name(), "simple");
+    assert_eq!(MyBox(Simple(3)).consume(), 30);
+
+    let mut inner = Simple(5);
+    let mut arena = ArenaBox(&mut inner);
+    arena.increment();
+    assert_eq!(arena.get(), 6);
+
+    let shared = shared::Shared(Rc::new(Simple(4)));
+    assert_eq!(shared.consume(), 4);
+}
@@ -0,0 +1,112 @@
+use std::pin::Pin;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(
+    delegate(Instrumented => self.inner),
+    delegate(Logged<'_> => self.1),
+    delegate(Typed => self.store: MemStore),
+)]
+trait Store {
+    fn get(&self, key: u32) -> Option<u32>;
+    fn put(&mut self, key: u32, value: u32) -> &mut Self;
+    fn merge(&mut self, other: &Self);
+    fn pinned_len(self: Pin<&Self>) -> usize;
+    fn into_values(self) -> Vec<u32>;
+
+    #[auto_impl(keep_default_for(delegate(Instrumented)))]
+    fn describe(&self) -> String {
+        "store".into()
+    }
+}
+
+#[auto_impl(delegate(Typed => self.store: MemStore))]
+trait Named {
+    const KIND: &'static str;
+    type Key;
+
+    fn name() -> &'static str;
+    fn first_key(&self) -> Option<Self::Key>;
+}
+
+#[derive(Default)]
+struct MemStore(Vec<(u32, u32)>);
+
+impl Store for MemStore {
+    fn get(&self, key: u32) -> Option<u32> {
+        self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+    fn put(&mut self, key: u32, value: u32) -> &mut Self {
+        self.0.push((key, value));
+        self
+    }
+    fn merge(&mut self, other: &Self) {
+        self.0.extend(other.0.iter().copied());
+    }
+    fn pinned_len(self: Pin<&Self>) -> usize {
+        self.0.len()
+    }
+    fn into_values(self) -> Vec<u32> {
+        self.0.into_iter().map(|(_, v)| v).collect()
+    }
+    fn describe(&self) -> String {
+        "memory".into()
+    }
+}
+
+impl Named for MemStore {
+    const KIND: &'static str = "memory";
+    type Key = u32;
+
+    fn name() -> &'static str {
+        "mem"
+    }
+    fn first_key(&self) -> Option<u32> {
+        self.0.first().map(|(k, _)| *k)
+    }
+}
+
+#[derive(Default)]
+struct Instrumented {
+    inner: MemStore,
+}
+
+struct Logged<'a>(&'a mut Vec<String>, MemStore);
+
+impl Logged<'_> {
+    fn new(log: &mut Vec<String>) -> Logged<'_> {
+        Logged(log, MemStore::default())
+    }
+}
+
+#[derive(Default)]
+struct Typed {
+    store: MemStore,
+}
+
+
+fn main() {
+    let mut a = Instrumented::default();
+    a.put(1, 10).put(2, 20);
+    a.merge(&Instrumented { inner: MemStore(vec![(3, 30)]) });
+    assert_eq!(a.get(2), Some(20));
+    assert_eq!(Pin::new(&a).pinned_len(), 3);
+    assert_eq!(a.describe(), "store");
+    assert_eq!(a.into_values(), vec![10, 20, 30]);
+
+    let mut log = Vec::new();
+    let mut logged = Logged::new(&mut log);
+    logged.put(5, 50);
+    logged.0.push("put".into());
+    assert_eq!(logged.get(5), Some(50));
+    assert_eq!(log, vec!["put".to_owned()]);
+
+    let mut typed = Typed::default();
+    typed.put(4, 40);
+    assert_eq!(typed.describe(), "memory");
+    assert_eq!(<Typed as Named>::KIND, "memory");
+    assert_eq!(<Typed as Named>::name(), "mem");
+    let key: Option<<Typed as Named>::Key> = typed.first_key();
+    assert_eq!(key, Some(4));
+}
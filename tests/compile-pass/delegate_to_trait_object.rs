@@ -0,0 +1,55 @@
+use auto_impl::auto_impl;
+
+
+// The field is a `Box<dyn Storage>`, so the trait has to be implemented for
+// `Box` as well.
+#[auto_impl(Box, delegate(Instrumented => self.inner), delegate(Wrapped => self.0))]
+trait Storage {
+    fn get(&self, key: u32) -> Option<u32>;
+    fn put(&mut self, key: u32, value: u32);
+
+    #[auto_impl(keep_default_for(delegate(Instrumented)))]
+    fn describe(&self) -> String {
+        "storage".into()
+    }
+}
+
+#[derive(Default)]
+struct MemStorage(Vec<(u32, u32)>);
+
+impl Storage for MemStorage {
+    fn get(&self, key: u32) -> Option<u32> {
+        self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+    fn put(&mut self, key: u32, value: u32) {
+        self.0.push((key, value));
+    }
+    fn describe(&self) -> String {
+        "memory".into()
+    }
+}
+
+struct Instrumented {
+    inner: Box<dyn Storage>,
+    calls: u32,
+}
+
+struct Wrapped(Box<dyn Storage>);
+
+
+fn main() {
+    let mut a = Instrumented {
+        inner: Box::new(MemStorage::default()),
+        calls: 0,
+    };
+    a.put(1, 10);
+    a.calls += 1;
+    assert_eq!(a.get(1), Some(10));
+    assert_eq!(a.describe(), "storage");
+    assert_eq!(a.calls, 1);
+
+    let mut w = Wrapped(Box::new(MemStorage::default()));
+    w.put(2, 20);
+    assert_eq!(w.get(2), Some(20));
+    assert_eq!(w.describe(), "memory");
+}
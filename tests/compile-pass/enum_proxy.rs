@@ -0,0 +1,100 @@
+use std::pin::Pin;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(enum AnyStore(MemStore, disk::DiskStore))]
+pub trait Store<K> {
+    type Error;
+
+    fn get(&self, key: K) -> Option<String>;
+    fn put(&mut self, key: K, value: String) -> Result<(), Self::Error>;
+    fn with_capacity(&mut self, capacity: usize) -> &mut Self;
+    fn pinned(self: Pin<&Self>) -> &'static str;
+    fn duplicate(&self) -> Self;
+    fn into_keys(self) -> Vec<K>;
+
+    #[auto_impl(keep_default_for(enum AnyStore))]
+    fn describe(&self) -> &'static str {
+        "store"
+    }
+}
+
+#[derive(Default)]
+pub struct MemStore(Vec<u32>);
+
+impl Store<u32> for MemStore {
+    type Error = String;
+
+    fn get(&self, key: u32) -> Option<String> {
+        self.0.iter().find(|&&k| k == key).map(|_| "mem".into())
+    }
+    fn put(&mut self, key: u32, _: String) -> Result<(), String> {
+        self.0.push(key);
+        Ok(())
+    }
+    fn with_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.0.reserve(capacity);
+        self
+    }
+    fn pinned(self: Pin<&Self>) -> &'static str {
+        "mem"
+    }
+    fn duplicate(&self) -> Self {
+        MemStore(self.0.clone())
+    }
+    fn into_keys(self) -> Vec<u32> {
+        self.0
+    }
+}
+
+mod disk {
+    use super::*;
+
+    pub struct DiskStore;
+
+    impl Store<u32> for DiskStore {
+        type Error = String;
+
+        fn get(&self, _: u32) -> Option<String> {
+            None
+        }
+        fn put(&mut self, _: u32, _: String) -> Result<(), String> {
+            Err("read-only".into())
+        }
+        fn with_capacity(&mut self, _: usize) -> &mut Self {
+            self
+        }
+        fn pinned(self: Pin<&Self>) -> &'static str {
+            "disk"
+        }
+        fn duplicate(&self) -> Self {
+            DiskStore
+        }
+        fn into_keys(self) -> Vec<u32> {
+            vec![]
+        }
+    }
+}
+
+fn put_twice<S: Store<u32, Error = String>>(store: &mut S) -> Result<(), String> {
+    store.put(1, "a".into())?;
+    store.put(2, "b".into())
+}
+
+
+fn main() {
+    let mut mem: AnyStore = MemStore::default().into();
+    let mut disk = AnyStore::from(disk::DiskStore);
+
+    assert_eq!(put_twice(mem.with_capacity(2)), Ok(()));
+    assert_eq!(put_twice(&mut disk), Err("read-only".to_string()));
+    assert_eq!(mem.get(1), Some("mem".to_string()));
+    assert_eq!(disk.get(1), None);
+    assert_eq!(Pin::new(&disk).pinned(), "disk");
+
+    assert!(matches!(mem.duplicate(), AnyStore::MemStore(_)));
+    assert!(matches!(disk.duplicate(), AnyStore::DiskStore(_)));
+    assert_eq!(mem.describe(), "store");
+    assert_eq!(mem.into_keys(), vec![1, 2]);
+}
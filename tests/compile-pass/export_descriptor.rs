@@ -0,0 +1,81 @@
+use auto_impl::{auto_impl, Delegate};
+
+
+#[auto_impl(&mut, export_descriptor)]
+pub trait Store {
+    fn get(&self, key: u32) -> Option<u32>;
+    fn put(&mut self, key: u32, value: u32);
+
+    #[auto_impl(keep_default_for(&mut))]
+    fn describe(&self) -> String {
+        "store".into()
+    }
+}
+
+#[auto_impl(export_descriptor)]
+pub trait Named {
+    const KIND: &'static str;
+    fn name() -> String;
+}
+
+#[derive(Default)]
+pub struct MemStore(Vec<(u32, u32)>);
+
+impl Store for MemStore {
+    fn get(&self, key: u32) -> Option<u32> {
+        self.0.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+    fn put(&mut self, key: u32, value: u32) {
+        self.0.push((key, value));
+    }
+    fn describe(&self) -> String {
+        "memory".into()
+    }
+}
+
+impl Named for MemStore {
+    const KIND: &'static str = "memory";
+    fn name() -> String {
+        "mem".into()
+    }
+}
+
+#[derive(Delegate, Default)]
+#[delegate(Store)]
+#[delegate(Named)]
+struct Newtype(MemStore);
+
+#[derive(Delegate, Default)]
+#[delegate(Store, to = inner)]
+#[delegate(Named, to = inner)]
+struct Instrumented<S: Default> {
+    inner: S,
+    calls: u32,
+}
+
+#[derive(Delegate)]
+#[delegate(Store)]
+enum AnyStore {
+    Mem(MemStore),
+    Wrapped(Newtype),
+}
+
+
+fn main() {
+    let mut a = Newtype::default();
+    a.put(1, 10);
+    assert_eq!(a.get(1), Some(10));
+    assert_eq!(a.describe(), "memory");
+    assert_eq!(<Newtype as Named>::KIND, "memory");
+
+    let mut b = Instrumented::<MemStore>::default();
+    b.put(2, 20);
+    b.calls += 1;
+    assert_eq!(b.get(2), Some(20));
+    assert_eq!(<Instrumented<MemStore> as Named>::name(), "mem");
+
+    let mut c = AnyStore::Wrapped(a);
+    c.put(3, 30);
+    assert_eq!(c.get(3), Some(30));
+    assert!(AnyStore::Mem(MemStore::default()).get(3).is_none());
+}
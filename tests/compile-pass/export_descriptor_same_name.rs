@@ -0,0 +1,60 @@
+use auto_impl::{auto_impl, Delegate};
+
+
+mod v1 {
+    use auto_impl::auto_impl;
+
+    #[auto_impl(export_descriptor)]
+    pub trait Store {
+        fn get(&self) -> u32;
+    }
+}
+
+mod v2 {
+    use auto_impl::auto_impl;
+
+    #[auto_impl(&, export_descriptor(name = StoreV2))]
+    pub trait Store {
+        fn get(&self, key: u32) -> Option<u32>;
+    }
+}
+
+// A trait at the root of the crate with the same name as the ones above.
+#[auto_impl(export_descriptor(name = RootStore))]
+pub trait Store {
+    fn len(&self) -> usize;
+}
+
+struct Mem;
+
+impl v1::Store for Mem {
+    fn get(&self) -> u32 {
+        1
+    }
+}
+
+impl v2::Store for Mem {
+    fn get(&self, key: u32) -> Option<u32> {
+        Some(key * 2)
+    }
+}
+
+impl Store for Mem {
+    fn len(&self) -> usize {
+        3
+    }
+}
+
+#[derive(Delegate)]
+#[delegate(v1::Store)]
+#[delegate(crate::v2::Store)]
+#[delegate(Store)]
+struct Wrapper(Mem);
+
+
+fn main() {
+    let w = Wrapper(Mem);
+    assert_eq!(v1::Store::get(&w), 1);
+    assert_eq!(v2::Store::get(&w, 2), Some(4));
+    assert_eq!(Store::len(&w), 3);
+}
@@ -0,0 +1,70 @@
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Box where T: Clone, Arc where T: Send + Sync, Rc)]
+trait Worker {
+    fn work(&self) -> u32;
+}
+
+#[auto_impl(unsafe(&, Box) where T: Sync + 'static)]
+unsafe trait Shared {
+    fn id(&self) -> u32;
+}
+
+#[auto_impl(Box where T::Item: Copy)]
+trait Source {
+    type Item;
+
+    fn peek_first(&self) -> Option<Self::Item>;
+}
+
+#[derive(Clone)]
+struct Plain(u32);
+
+impl Worker for Plain {
+    fn work(&self) -> u32 {
+        self.0
+    }
+}
+
+unsafe impl Shared for Plain {
+    fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+struct Counter(Vec<u32>);
+
+impl Source for Counter {
+    type Item = u32;
+
+    fn peek_first(&self) -> Option<u32> {
+        self.0.last().copied()
+    }
+}
+
+// Neither `Clone` nor `Sync`, only usable with `&` and `Rc`.
+struct Local(Cell<u32>);
+
+impl Worker for Local {
+    fn work(&self) -> u32 {
+        self.0.get()
+    }
+}
+
+fn work<W: Worker>(w: W) -> u32 {
+    w.work()
+}
+
+fn id<S: Shared>(s: S) -> u32 {
+    s.id()
+}
+
+fn main() {
+    assert_eq!(work(Box::new(Plain(1))) + work(Arc::new(Plain(2))), 3);
+    assert_eq!(work(&Local(Cell::new(4))) + work(Rc::new(Local(Cell::new(5)))), 9);
+    assert_eq!(id(&Plain(6)) + id(Box::new(Plain(7))), 13);
+    assert_eq!(Box::new(Counter(vec![8])).peek_first(), Some(8));
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use auto_impl::auto_impl;
+
+
+struct Event(u32);
+
+#[auto_impl(Vec, slice, array)]
+trait Listener {
+    fn on_event(&mut self, e: &Event);
+    fn with_offset(&mut self, offset: u32) -> &mut Self;
+    fn kind() -> &'static str;
+
+    #[auto_impl(fan_out = first_some)]
+    fn find(&self, id: u32) -> Option<u32>;
+
+    #[auto_impl(fan_out = all)]
+    fn is_ready(&self) -> bool;
+
+    #[auto_impl(fan_out = any)]
+    fn has_seen(&self, id: u32) -> bool;
+
+    #[auto_impl(fan_out = collect)]
+    fn seen(&self) -> HashSet<u32>;
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    offset: u32,
+    seen: Vec<u32>,
+}
+
+impl Listener for Recorder {
+    fn on_event(&mut self, e: &Event) {
+        self.seen.push(e.0 + self.offset);
+    }
+    fn with_offset(&mut self, offset: u32) -> &mut Self {
+        self.offset = offset;
+        self
+    }
+    fn kind() -> &'static str {
+        "recorder"
+    }
+    fn find(&self, id: u32) -> Option<u32> {
+        self.seen.iter().copied().find(|&x| x == id)
+    }
+    fn is_ready(&self) -> bool {
+        !self.seen.is_empty()
+    }
+    fn has_seen(&self, id: u32) -> bool {
+        self.seen.contains(&id)
+    }
+    fn seen(&self) -> HashSet<u32> {
+        self.seen.iter().copied().collect()
+    }
+}
+
+#[auto_impl(Vec, array)]
+trait Consume {
+    fn consume(self);
+}
+
+impl Consume for Recorder {
+    fn consume(self) {}
+}
+
+#[auto_impl(Vec)]
+trait Drain {
+    #[auto_impl(fan_out = collect)]
+    fn drain(self) -> Vec<u32>;
+}
+
+impl Drain for u32 {
+    fn drain(self) -> Vec<u32> {
+        vec![self]
+    }
+}
+
+
+fn main() {
+    let mut listeners = vec![Recorder::default(), Recorder::default()];
+    listeners.with_offset(10).on_event(&Event(1));
+    listeners[1].on_event(&Event(2));
+    assert_eq!(listeners[0].seen, vec![11]);
+    assert_eq!(listeners[1].seen, vec![11, 12]);
+    assert_eq!(<Vec<Recorder> as Listener>::kind(), "recorder");
+
+    assert_eq!(listeners.find(12), Some(12));
+    assert_eq!(listeners.find(3), None);
+    assert!(listeners.is_ready());
+    assert!(listeners.has_seen(12));
+    assert_eq!(listeners.seen().len(), 2);
+
+    let slice: &mut [Recorder] = &mut listeners;
+    slice.on_event(&Event(0));
+    assert!(slice.has_seen(10));
+
+    let mut array = [Recorder::default(), Recorder::default(), Recorder::default()];
+    array.on_event(&Event(5));
+    assert!(array.is_ready());
+    assert_eq!(array.seen().into_iter().collect::<Vec<_>>(), vec![5]);
+
+    let empty: [Recorder; 0] = [];
+    assert!(empty.is_ready());
+    assert!(!empty.has_seen(5));
+
+    vec![listeners[0].clone()].consume();
+    [listeners[0].clone(), listeners[1].clone()].consume();
+    assert_eq!(vec![1u32, 2].drain(), vec![1, 2]);
+}
@@ -0,0 +1,78 @@
+use std::rc::Rc;
+
+use auto_impl::auto_impl;
+
+
+struct Bag(Vec<u32>);
+
+#[auto_impl(&mut, Box)]
+impl Extend<u32> for Bag {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[auto_impl(&, &mut, Box)]
+impl PartialEq<u32> for Bag {
+    fn eq(&self, other: &u32) -> bool {
+        self.0.len() as u32 == *other
+    }
+}
+
+trait Shape {
+    const SIDES: u32;
+    type Unit;
+
+    fn area(&self) -> f64;
+    fn name() -> &'static str;
+    fn grow(&mut self, _by: f64) {}
+
+    fn describe(&self) -> String {
+        format!("{} with {} sides", Self::name(), Self::SIDES)
+    }
+}
+
+struct Square(f64);
+
+#[auto_impl(&, Box, Rc)]
+impl Shape for Square {
+    const SIDES: u32 = 4;
+    type Unit = f64;
+
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+    fn name() -> &'static str {
+        "square"
+    }
+
+    #[auto_impl(keep_default_for(&, Rc))]
+    fn grow(&mut self, by: f64) {
+        self.0 += by;
+    }
+
+    #[auto_impl(keep_default_for(&))]
+    fn describe(&self) -> String {
+        "a square".into()
+    }
+}
+
+
+fn main() {
+    let mut bag = Bag(vec![]);
+    (&mut bag).extend(vec![1, 2]);
+    let mut boxed = Box::new(bag);
+    <Box<Bag> as Extend<u32>>::extend(&mut boxed, Some(3));
+    let bag = *boxed;
+    assert_eq!(bag.0, vec![1, 2, 3]);
+    assert!(&bag == 3);
+    assert!(Box::new(Bag(vec![])) == 0);
+
+    let square = Square(2.0);
+    assert_eq!((&square).area(), 4.0);
+    assert_eq!(<&Square as Shape>::SIDES, 4);
+    let _: <Rc<Square> as Shape>::Unit = 1.0;
+    assert_eq!(<&Square as Shape>::describe(&&square), "square with 4 sides");
+    assert_eq!(Box::new(Square(1.0)).describe(), "a square");
+    assert_eq!(<Rc<Square> as Shape>::name(), "square");
+}
@@ -0,0 +1,47 @@
+use std::{rc::Rc, sync::Arc};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, &mut)]
+#[cfg_attr(all(), auto_impl(Box, Arc))]
+#[cfg_attr(any(), auto_impl(Rc))]
+trait Foo {
+    fn get(&self) -> u32;
+
+    #[auto_impl(keep_default_for(&, Arc))]
+    fn name(&self) -> &'static str {
+        "default"
+    }
+}
+
+#[auto_impl(Rc)]
+trait Bar {}
+
+struct S;
+
+impl Foo for S {
+    fn get(&self) -> u32 {
+        1
+    }
+
+    fn name(&self) -> &'static str {
+        "s"
+    }
+}
+
+impl Bar for S {}
+
+fn assert_foo<T: Foo>(t: T) -> u32 {
+    t.get()
+}
+
+fn assert_bar<T: Bar>(_: T) {}
+
+fn main() {
+    assert_eq!(assert_foo(&mut S) + assert_foo(Box::new(S)), 2);
+    assert_eq!(Foo::name(&&S), "default");
+    assert_eq!(Foo::name(&Box::new(S)), "s");
+    assert_eq!(Arc::new(S).name(), "default");
+    assert_bar(Rc::new(S));
+}
@@ -0,0 +1,85 @@
+use std::pin::Pin;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Option)]
+trait Hook {
+    fn on_event(&mut self, id: u32);
+    fn priority(&self) -> u32;
+    fn name(&self) -> String;
+
+    #[auto_impl(on_none = "disabled")]
+    fn label(&self) -> &str;
+
+    #[auto_impl(on_none = Err(id))]
+    fn check(&self, id: u32) -> Result<u32, u32>;
+
+    fn finish(self) -> Vec<u32>;
+    fn pinned(self: Pin<&Self>) -> bool;
+    fn with_priority(&mut self, priority: u32) -> &mut Self;
+    fn create() -> Self;
+    fn duplicate(&self) -> Self;
+}
+
+struct Recorder {
+    events: Vec<u32>,
+    priority: u32,
+}
+
+impl Hook for Recorder {
+    fn on_event(&mut self, id: u32) {
+        self.events.push(id);
+    }
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+    fn name(&self) -> String {
+        "recorder".into()
+    }
+    fn label(&self) -> &str {
+        "enabled"
+    }
+    fn check(&self, id: u32) -> Result<u32, u32> {
+        Ok(id)
+    }
+    fn finish(self) -> Vec<u32> {
+        self.events
+    }
+    fn pinned(self: Pin<&Self>) -> bool {
+        true
+    }
+    fn with_priority(&mut self, priority: u32) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+    fn create() -> Self {
+        Recorder { events: vec![], priority: 0 }
+    }
+    fn duplicate(&self) -> Self {
+        Recorder { events: self.events.clone(), priority: self.priority }
+    }
+}
+
+
+fn main() {
+    let mut some: Option<Recorder> = Hook::create();
+    some.with_priority(3).on_event(1);
+    assert_eq!(some.priority(), 3);
+    assert_eq!(some.name(), "recorder");
+    assert_eq!(some.label(), "enabled");
+    assert_eq!(some.check(4), Ok(4));
+    assert!(Pin::new(&some).pinned());
+    assert!(some.duplicate().is_some());
+    assert_eq!(some.finish(), vec![1]);
+
+    let mut none: Option<Recorder> = None;
+    none.with_priority(3).on_event(1);
+    assert_eq!(none.priority(), 0);
+    assert_eq!(none.name(), "");
+    assert_eq!(none.label(), "disabled");
+    assert_eq!(none.check(4), Err(4));
+    assert!(!Pin::new(&none).pinned());
+    assert!(none.duplicate().is_none());
+    assert_eq!(none.finish(), Vec::<u32>::new());
+}
@@ -0,0 +1,57 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&mut, Box, Pin)]
+trait AsyncSource {
+    type Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+
+    fn size_hint(&self) -> (usize, Option<usize>);
+}
+
+#[auto_impl(&, Rc, Arc, Pin)]
+trait Peek {
+    fn peek(self: Pin<&Self>) -> Option<u8>;
+}
+
+
+fn assert_source<T: AsyncSource>() {}
+fn assert_peek<T: Peek>() {}
+
+struct Counter;
+
+impl AsyncSource for Counter {
+    type Item = u32;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(None)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(0))
+    }
+}
+
+impl Peek for Counter {
+    fn peek(self: Pin<&Self>) -> Option<u8> {
+        None
+    }
+}
+
+fn main() {
+    assert_source::<Pin<Box<Counter>>>();
+    assert_source::<Pin<&mut Counter>>();
+    assert_source::<Pin<Box<dyn AsyncSource<Item = u32>>>>();
+    assert_source::<&mut Counter>();
+    assert_source::<Box<Counter>>();
+
+    assert_peek::<Pin<&Counter>>();
+    assert_peek::<std::rc::Rc<Counter>>();
+    assert_peek::<&Counter>();
+}
@@ -0,0 +1,27 @@
+use std::rc::Rc;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, Box, Rc)]
+trait Merge {
+    fn merge_opt(&self, x: Option<&Self>, y: u32) -> u32;
+    fn merge_both(&self, y: Option<&Self>, x: u32) -> u32;
+}
+
+struct Num(u32);
+
+impl Merge for Num {
+    fn merge_opt(&self, x: Option<&Self>, y: u32) -> u32 {
+        self.0 + x.map_or(0, |o| o.0) + y
+    }
+    fn merge_both(&self, y: Option<&Self>, x: u32) -> u32 {
+        self.0 + y.map_or(0, |o| o.0) * x
+    }
+}
+
+fn main() {
+    assert_eq!((&Num(1)).merge_opt(Some(&&Num(2)), 3), 6);
+    assert_eq!(Box::new(Num(1)).merge_both(Some(&Box::new(Num(2))), 10), 21);
+    assert_eq!(Rc::new(Num(1)).merge_both(None, 10), 1);
+}
@@ -0,0 +1,59 @@
+use std::rc::Rc;
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, &mut, Box, Rc, Arc, Pin)]
+trait Merge {
+    fn merge(&self, other: &Self) -> u32;
+    fn merge_opt(&self, other: Option<&Self>) -> u32;
+    fn cmp_with<'a>(&self, other: &'a Self, item: <Self as Merge>::Item) -> bool;
+
+    type Item;
+}
+
+#[auto_impl(&mut, Box)]
+trait Absorb {
+    fn absorb(&mut self, other: &mut Self);
+    fn absorb_opt(&mut self, other: Option<&mut Self>);
+}
+
+#[auto_impl(Box)]
+trait Combine {
+    fn combine(self, other: Self) -> u32;
+    fn combine_opt(self, other: Option<Self>) -> u32 where Self: Sized;
+}
+
+
+struct Num(u32);
+
+impl Merge for Num {
+    type Item = ();
+
+    fn merge(&self, other: &Self) -> u32 {
+        self.0 + other.0
+    }
+    fn merge_opt(&self, other: Option<&Self>) -> u32 {
+        self.0 + other.map_or(0, |o| o.0)
+    }
+    fn cmp_with<'a>(&self, other: &'a Self, _: ()) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Combine for Num {
+    fn combine(self, other: Self) -> u32 {
+        self.0 + other.0
+    }
+    fn combine_opt(self, other: Option<Self>) -> u32 {
+        self.0 + other.map_or(0, |o| o.0)
+    }
+}
+
+fn main() {
+    assert_eq!((&Num(1)).merge(&&Num(2)), 3);
+    assert_eq!(Rc::new(Num(1)).merge_opt(Some(&Rc::new(Num(2)))), 3);
+    assert!(Box::new(Num(1)).cmp_with(&Box::new(Num(1)), ()));
+    assert_eq!(Box::new(Num(1)).combine(Box::new(Num(2))), 3);
+    assert_eq!(Box::new(Num(1)).combine_opt(None), 1);
+}
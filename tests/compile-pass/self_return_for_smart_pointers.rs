@@ -0,0 +1,42 @@
+use std::{rc::Rc, sync::Arc};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(Box, Rc, Arc)]
+trait Factory {
+    fn new(cfg: u32) -> Self;
+    fn clone_it(&self) -> Self;
+    fn maybe_new(cfg: u32) -> Option<Self> where Self: Sized;
+    fn try_new(cfg: u32) -> Result<Self, String> where Self: Sized;
+    fn cfg(&self) -> u32;
+}
+
+
+#[derive(Clone)]
+struct Config(u32);
+
+impl Factory for Config {
+    fn new(cfg: u32) -> Self {
+        Config(cfg)
+    }
+    fn clone_it(&self) -> Self {
+        self.clone()
+    }
+    fn maybe_new(cfg: u32) -> Option<Self> {
+        Some(Config(cfg))
+    }
+    fn try_new(cfg: u32) -> Result<Self, String> {
+        Err(cfg.to_string())
+    }
+    fn cfg(&self) -> u32 {
+        self.0
+    }
+}
+
+fn main() {
+    let boxed = <Box<Config> as Factory>::new(3);
+    assert_eq!(boxed.clone_it().cfg(), 3);
+    assert_eq!(<Rc<Config> as Factory>::maybe_new(4).unwrap().cfg(), 4);
+    assert!(<Arc<Config> as Factory>::try_new(5).is_err());
+}
@@ -0,0 +1,70 @@
+use std::{
+    cmp::Reverse,
+    mem::ManuallyDrop,
+    num::Wrapping,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(ManuallyDrop, AssertUnwindSafe, Wrapping, Reverse)]
+trait Counter {
+    fn new(start: u32) -> Self;
+    fn get(&self) -> u32;
+    fn increment(&mut self);
+    fn pinned_get(self: Pin<&Self>) -> u32;
+    fn pinned_increment(self: Pin<&mut Self>);
+    fn max(self, other: Self) -> Self;
+    fn same_as(&self, other: &Self) -> bool;
+}
+
+#[derive(Debug, PartialEq)]
+struct Simple(u32);
+
+impl Counter for Simple {
+    fn new(start: u32) -> Self {
+        Simple(start)
+    }
+    fn get(&self) -> u32 {
+        self.0
+    }
+    fn increment(&mut self) {
+        self.0 += 1;
+    }
+    fn pinned_get(self: Pin<&Self>) -> u32 {
+        self.0
+    }
+    fn pinned_increment(self: Pin<&mut Self>) {
+        self.get_mut().0 += 1;
+    }
+    fn max(self, other: Self) -> Self {
+        if self.0 >= other.0 { self } else { other }
+    }
+    fn same_as(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+fn bump<C: Counter + Unpin>(mut c: C) -> u32 {
+    c.increment();
+    Pin::new(&mut c).pinned_increment();
+    Pin::new(&c).pinned_get()
+}
+
+
+fn main() {
+    assert_eq!(bump(ManuallyDrop::new(Simple(0))), 2);
+    assert_eq!(bump(AssertUnwindSafe(Simple(1))), 3);
+    assert_eq!(bump(Wrapping(Simple(2))), 4);
+    assert_eq!(bump(Reverse(Simple(3))), 5);
+
+    let a: Reverse<Simple> = Counter::new(4);
+    assert_eq!(a.get(), 4);
+    assert!(a.same_as(&Reverse(Simple(4))));
+    assert_eq!(a.max(Reverse(Simple(7))).0, Simple(7));
+
+    let b: ManuallyDrop<Simple> = Counter::new(5);
+    assert_eq!(ManuallyDrop::into_inner(b.max(ManuallyDrop::new(Simple(1)))), Simple(5));
+}
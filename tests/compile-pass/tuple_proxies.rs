@@ -0,0 +1,104 @@
+use auto_impl::auto_impl;
+
+
+struct Event(u32);
+
+#[auto_impl(tuples(1..=12))]
+trait Listener {
+    fn on_event(&mut self, e: &Event);
+    fn with_offset(&mut self, offset: u32) -> &mut Self;
+
+    #[auto_impl(fan_out = first_some)]
+    fn find(&self, id: u32) -> Option<String>;
+
+    #[auto_impl(fan_out = all)]
+    fn is_ready(&self) -> bool;
+
+    #[auto_impl(fan_out = any)]
+    fn has_seen(&self, id: u32) -> bool;
+
+    #[auto_impl(fan_out = collect)]
+    fn names(&self) -> Vec<String>;
+
+    fn close(self);
+}
+
+#[derive(Default)]
+struct Logger {
+    offset: u32,
+    seen: Vec<u32>,
+}
+
+impl Listener for Logger {
+    fn on_event(&mut self, e: &Event) {
+        self.seen.push(e.0 + self.offset);
+    }
+    fn with_offset(&mut self, offset: u32) -> &mut Self {
+        self.offset = offset;
+        self
+    }
+    fn find(&self, id: u32) -> Option<String> {
+        self.seen.iter().find(|&&x| x == id).map(|x| format!("logger {}", x))
+    }
+    fn is_ready(&self) -> bool {
+        true
+    }
+    fn has_seen(&self, id: u32) -> bool {
+        self.seen.contains(&id)
+    }
+    fn names(&self) -> Vec<String> {
+        vec!["logger".into()]
+    }
+    fn close(self) {}
+}
+
+#[derive(Default)]
+struct Metrics {
+    count: u32,
+}
+
+impl Listener for Metrics {
+    fn on_event(&mut self, _: &Event) {
+        self.count += 1;
+    }
+    fn with_offset(&mut self, _: u32) -> &mut Self {
+        self
+    }
+    fn find(&self, _: u32) -> Option<String> {
+        Some("metrics".into())
+    }
+    fn is_ready(&self) -> bool {
+        self.count > 0
+    }
+    fn has_seen(&self, _: u32) -> bool {
+        false
+    }
+    fn names(&self) -> Vec<String> {
+        vec!["metrics".into(), format!("{} events", self.count)]
+    }
+    fn close(self) {}
+}
+
+
+fn main() {
+    let mut listeners = (Logger::default(), Metrics::default());
+    assert!(!listeners.is_ready());
+    listeners.with_offset(10).on_event(&Event(1));
+    assert_eq!(listeners.0.seen, vec![11]);
+    assert_eq!(listeners.1.count, 1);
+
+    assert!(listeners.is_ready());
+    assert!(listeners.has_seen(11));
+    assert_eq!(listeners.find(11), Some("logger 11".to_string()));
+    assert_eq!(listeners.find(2), Some("metrics".to_string()));
+    assert_eq!(listeners.names(), vec!["logger", "metrics", "1 events"]);
+    listeners.close();
+
+    let mut single = (Metrics::default(),);
+    single.on_event(&Event(0));
+    assert!(single.is_ready());
+
+    let mut nested = (Logger::default(), (Metrics::default(), Logger::default()));
+    nested.on_event(&Event(3));
+    assert_eq!((nested.1).1.seen, vec![3]);
+}
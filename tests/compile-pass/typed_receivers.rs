@@ -0,0 +1,65 @@
+use std::{rc::Rc, sync::Arc};
+
+use auto_impl::auto_impl;
+
+
+#[auto_impl(&, &mut, Box)]
+trait Typed {
+    fn by_ref(self: &Self) -> u32;
+    fn by_ref_with_lifetime<'a>(self: &'a Self) -> &'a str;
+}
+
+#[auto_impl(&mut, Box)]
+trait TypedMut {
+    fn by_mut(self: &mut Self);
+}
+
+#[auto_impl(Box)]
+trait Boxed {
+    fn into_inner(self: Box<Self>) -> String;
+    fn by_value(self: Self) -> String where Self: Sized;
+}
+
+#[auto_impl(Rc)]
+trait Counted {
+    fn count(self: Rc<Self>) -> usize;
+}
+
+#[auto_impl(Arc)]
+trait Shared {
+    fn share(self: std::sync::Arc<Self>) -> usize;
+}
+
+
+fn assert_boxed<T: Boxed + ?Sized>() {}
+
+struct Foo;
+
+impl Boxed for Foo {
+    fn into_inner(self: Box<Self>) -> String {
+        String::new()
+    }
+    fn by_value(self: Self) -> String {
+        String::new()
+    }
+}
+
+impl Counted for Foo {
+    fn count(self: Rc<Self>) -> usize {
+        Rc::strong_count(&self)
+    }
+}
+
+impl Shared for Foo {
+    fn share(self: Arc<Self>) -> usize {
+        Arc::strong_count(&self)
+    }
+}
+
+fn main() {
+    assert_boxed::<Box<Foo>>();
+    assert_boxed::<dyn Boxed>();
+
+    assert_eq!(Rc::new(Rc::new(Foo)).count(), 1);
+    assert_eq!(Arc::new(Arc::new(Foo)).share(), 1);
+}
@@ -0,0 +1,34 @@
+use std::rc::Rc;
+
+use auto_impl::auto_impl;
+
+
+/// # Safety
+///
+/// `as_ptr` must return a pointer that is valid for reads of `len` bytes.
+#[auto_impl(unsafe(&, &mut, Box), unsafe(Rc))]
+unsafe trait RawBuffer {
+    fn as_ptr(&self) -> *const u8;
+    fn len(&self) -> usize;
+}
+
+unsafe impl RawBuffer for Vec<u8> {
+    fn as_ptr(&self) -> *const u8 {
+        Vec::as_ptr(self)
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+fn assert_raw_buffer<T: RawBuffer>() {}
+
+
+fn main() {
+    assert_raw_buffer::<Vec<u8>>();
+    assert_raw_buffer::<&Vec<u8>>();
+    assert_raw_buffer::<&mut Vec<u8>>();
+    assert_raw_buffer::<Box<Vec<u8>>>();
+    assert_raw_buffer::<Rc<Vec<u8>>>();
+}
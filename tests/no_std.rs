@@ -10,10 +10,35 @@ mod alloc {}
 struct Box;
 struct Rc;
 struct Arc;
+struct Cow;
+struct Pin;
+struct ManuallyDrop;
+struct AssertUnwindSafe;
+struct Wrapping;
+struct Reverse;
+struct Option;
+struct Vec;
 struct Fn;
 struct FnMut;
 
-#[auto_impl(&, &mut, Box, Rc, Arc)]
+#[auto_impl(
+    &,
+    &mut,
+    Box,
+    Rc,
+    Arc,
+    Cow,
+    Pin,
+    ManuallyDrop,
+    AssertUnwindSafe,
+    Wrapping,
+    Reverse,
+    Option,
+    Vec,
+    slice,
+    array,
+    tuples(1..=3)
+)]
 trait Test {}
 
 #[auto_impl(Fn)]
@@ -0,0 +1,18 @@
+use std::fmt::Debug;
+
+use auto_impl::auto_impl;
+
+
+trait Source {
+    type Item;
+}
+
+#[auto_impl(&, &mut, Box)]
+trait Factory {
+    fn create<O: Default>(&self, extra: impl Debug) -> O;
+    fn project<S: Source>(&self, item: S::Item, extra: impl Debug);
+    fn convert<V>(&self, from: impl Into<V>) -> V;
+}
+
+
+fn main() {}
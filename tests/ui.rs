@@ -20,6 +20,13 @@ fn ui_since_1_51_compile_pass() {
     t.pass("tests/since_1.51/compile-pass/*.rs");
 }
 
+#[rustversion::since(1.63)]
+#[test]
+fn ui_since_1_63_compile_pass() {
+    let t = TestCases::new();
+    t.pass("tests/since_1.63/compile-pass/*.rs");
+}
+
 #[rustversion::since(1.75)]
 #[test]
 fn ui_since_1_75_compile_pass() {